@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: MIT
+
+//! Counts the allocations and measures the time of parsing pattern strings.
+//!
+//! Run with `cargo bench --bench parse`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use url_pattern::{parse_pattern, Options};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const PATTERNS: [&str; 4] = [
+    "/tenants/:tenant/users/:id(\\d+)",
+    "/api/:version(v\\d+)/:resource/:id?/:rest*",
+    "{/:lang(en|de|fr)}?/docs/:section/:page(\\d+)?",
+    "/files/:path+.:ext(jpg|png|gif)",
+];
+
+const ITERATIONS: u32 = 10_000;
+
+fn main() {
+    let options = Options::pathname();
+
+    for pattern in PATTERNS {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        black_box(parse_pattern(black_box(pattern), &options).unwrap());
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            black_box(parse_pattern(black_box(pattern), &options).unwrap());
+        }
+        let elapsed = start.elapsed() / ITERATIONS;
+
+        println!("{pattern:<48} {allocations:>4} allocations {elapsed:>10.2?}");
+    }
+}
@@ -0,0 +1,117 @@
+/* SPDX-License-Identifier: MIT */
+/* Generated from src/ffi.rs by tests/ffi.rs, don't edit. */
+
+#ifndef URL_PATTERN_H
+#define URL_PATTERN_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct UrlPatternFfiError UrlPatternFfiError;
+typedef struct UrlPattern UrlPattern;
+typedef struct UrlPatternGroups UrlPatternGroups;
+
+// Compiles a constructor string like `https://:tenant.example.com/users/:id`,
+// relative to `base_url` unless it is NULL.
+//
+// Returns NULL on failure, and if `error` isn't NULL stores an error in it
+// that has to be released with `url_pattern_error_free`.
+//
+// # Safety
+//
+// `input` has to be a valid string, `base_url` NULL or a valid string and
+// `error` NULL or valid for writes.
+UrlPattern *url_pattern_new(const char *input, const char *base_url, bool ignore_case, UrlPatternFfiError **error);
+
+// Returns whether the URL `url` matches.
+//
+// Returns false if an argument is NULL or `url` isn't UTF-8, and if `error`
+// isn't NULL stores an error in it that has to be released with
+// `url_pattern_error_free`.
+//
+// # Safety
+//
+// `pattern` has to be NULL or returned by `url_pattern_new`, `url` NULL or a
+// valid string and `error` NULL or valid for writes.
+bool url_pattern_test(const UrlPattern *pattern, const char *url, UrlPatternFfiError **error);
+
+// Matches the URL `url` and returns an iterator over the groups of every
+// component, or NULL if it doesn't match. The iterator has to be released
+// with `url_pattern_groups_free`.
+//
+// Like `url_pattern_test`, stores an error in `error` for invalid arguments.
+//
+// # Safety
+//
+// `pattern` has to be NULL or returned by `url_pattern_new`, `url` NULL or a
+// valid string and `error` NULL or valid for writes.
+UrlPatternGroups *url_pattern_exec(const UrlPattern *pattern, const char *url, UrlPatternFfiError **error);
+
+// Advances to the next group and stores its component, name and value, or
+// returns false if there are no more groups. The value is NULL for an
+// optional group that didn't match.
+//
+// # Safety
+//
+// `groups` has to be returned by `url_pattern_exec`, and the other arguments
+// valid for writes.
+bool url_pattern_groups_next(UrlPatternGroups *groups, const char **component, const char **name, const char **value);
+
+// # Safety
+//
+// `groups` has to be NULL or returned by `url_pattern_exec`, and not be used
+// afterwards.
+void url_pattern_groups_free(UrlPatternGroups *groups);
+
+// # Safety
+//
+// `pattern` has to be NULL or returned by `url_pattern_new`, and not be used
+// afterwards.
+void url_pattern_free(UrlPattern *pattern);
+
+// A description of the error.
+//
+// # Safety
+//
+// `error` has to be an error stored by another function.
+const char *url_pattern_error_message(const UrlPatternFfiError *error);
+
+// The name of the component whose pattern is invalid, like `pathname`, or
+// NULL if the error isn't about a single component.
+//
+// # Safety
+//
+// `error` has to be an error stored by another function.
+const char *url_pattern_error_component(const UrlPatternFfiError *error);
+
+// The pattern of the component whose pattern is invalid, or NULL if the error
+// isn't about a single component.
+//
+// # Safety
+//
+// `error` has to be an error stored by another function.
+const char *url_pattern_error_pattern(const UrlPatternFfiError *error);
+
+// The byte offset in the pattern returned by `url_pattern_error_pattern` at
+// which parsing failed, or -1 if it is unknown.
+//
+// # Safety
+//
+// `error` has to be an error stored by another function.
+ptrdiff_t url_pattern_error_position(const UrlPatternFfiError *error);
+
+// # Safety
+//
+// `error` has to be NULL or an error stored by another function, and not be
+// used afterwards.
+void url_pattern_error_free(UrlPatternFfiError *error);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* URL_PATTERN_H */
@@ -0,0 +1,531 @@
+// SPDX-License-Identifier: MIT
+
+//! Finite automata for reasoning about the set of inputs a part list matches.
+//!
+//! The automata work on ranges of code points, so that wildcards don't need a
+//! transition for every character. Inputs are assumed to be URL components,
+//! which never contain ASCII tab or newline because the URL parser strips
+//! them, so the difference between `.` and `[^/]` for line terminators
+//! doesn't make patterns incomparable.
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use regex_syntax::hir::{Class, Hir, HirKind};
+use regex_syntax::ParserBuilder;
+use thiserror::Error;
+
+use crate::{Modifier, Options, Part};
+
+/// A construct that can't be turned into an automaton.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AnalysisError {
+    #[error("regexp group `{0}` can't be analyzed")]
+    UnsupportedRegExp(String),
+    #[error("group `{0}` has a constraint with a validation function, which can't be analyzed")]
+    Validated(String),
+}
+
+/// Inclusive ranges of code points.
+pub(crate) type CharClass = Vec<(u32, u32)>;
+
+const MAX_CHAR: u32 = char::MAX as u32;
+
+/// <https://infra.spec.whatwg.org/#ascii-tab-or-newline>
+const TAB_OR_NEWLINE: [char; 3] = ['\t', '\n', '\r'];
+
+/// A regular expression over character classes.
+#[derive(Debug, Clone)]
+pub(crate) enum Expr {
+    Empty,
+    Class(CharClass),
+    Concat(Vec<Expr>),
+    Alternation(Vec<Expr>),
+    /// Zero or more repetitions.
+    Repeat(Box<Expr>),
+}
+
+impl Expr {
+    fn optional(self) -> Expr {
+        Expr::Alternation(vec![self, Expr::Empty])
+    }
+
+    fn one_or_more(self) -> Expr {
+        Expr::Concat(vec![self.clone(), Expr::Repeat(Box::new(self))])
+    }
+
+    fn with_modifier(self, modifier: Option<Modifier>) -> Expr {
+        match modifier {
+            None => self,
+            Some(Modifier::Optional) => self.optional(),
+            Some(Modifier::ZeroOrMore) => Expr::Repeat(Box::new(self)),
+            Some(Modifier::OneOrMore) => self.one_or_more(),
+        }
+    }
+}
+
+/// Returns `chr` and the characters it matches case-insensitively.
+fn case_variants(chr: char, ignore_case: bool) -> Vec<char> {
+    let mut variants = vec![chr];
+    if ignore_case {
+        let lowercase: Vec<char> = chr.to_lowercase().collect();
+        let uppercase: Vec<char> = chr.to_uppercase().collect();
+        for mapped in [lowercase, uppercase] {
+            if let [mapped] = mapped[..] {
+                if !variants.contains(&mapped) {
+                    variants.push(mapped);
+                }
+            }
+        }
+    }
+    variants
+}
+
+fn literal(value: &str, options: &Options) -> Expr {
+    Expr::Concat(
+        value
+            .chars()
+            .map(|chr| {
+                Expr::Class(
+                    case_variants(chr, options.ignore_case)
+                        .into_iter()
+                        .map(|chr| (chr as u32, chr as u32))
+                        .collect(),
+                )
+            })
+            .collect(),
+    )
+}
+
+/// Removes the characters that can't appear in a URL component from `class`.
+fn url_characters(class: CharClass) -> CharClass {
+    let allowed = negated(&[]);
+    let mut result = vec![];
+    for (start, end) in class {
+        for &(low, high) in &allowed {
+            if start.max(low) <= end.min(high) {
+                result.push((start.max(low), end.min(high)));
+            }
+        }
+    }
+    result
+}
+
+/// The largest bounded repetition that is unrolled, like `{1000}`.
+const MAX_REPETITION: u32 = 100;
+
+/// Builds the expression for the regular expression of a regexp group.
+///
+/// Regexp groups are matched by the `regex` crate, so they are parsed with
+/// the same syntax. Assertions like `^` or `\b` depend on the surrounding
+/// input and are not supported.
+fn regexp_expr(hir: &Hir) -> Option<Expr> {
+    Some(match hir.kind() {
+        HirKind::Empty => Expr::Empty,
+        HirKind::Literal(literal) => Expr::Concat(
+            std::str::from_utf8(&literal.0)
+                .ok()?
+                .chars()
+                .map(|chr| Expr::Class(url_characters(vec![(chr as u32, chr as u32)])))
+                .collect(),
+        ),
+        HirKind::Class(Class::Unicode(class)) => Expr::Class(url_characters(
+            class
+                .iter()
+                .map(|range| (range.start() as u32, range.end() as u32))
+                .collect(),
+        )),
+        // An empty class, like `[^\s\S]`, is represented as an empty class of bytes.
+        HirKind::Class(Class::Bytes(class))
+            if class.ranges().iter().all(|range| range.end() < 0x80) =>
+        {
+            Expr::Class(url_characters(
+                class
+                    .iter()
+                    .map(|range| (range.start() as u32, range.end() as u32))
+                    .collect(),
+            ))
+        }
+        HirKind::Class(Class::Bytes(_)) | HirKind::Look(_) => return None,
+        HirKind::Repetition(repetition) => {
+            let max = repetition.max.unwrap_or(repetition.min);
+            if max > MAX_REPETITION {
+                return None;
+            }
+            let sub = regexp_expr(&repetition.sub)?;
+            let mut exprs = vec![sub.clone(); repetition.min as usize];
+            match repetition.max {
+                Some(max) => exprs.extend((repetition.min..max).map(|_| sub.clone().optional())),
+                None => exprs.push(Expr::Repeat(Box::new(sub))),
+            }
+            Expr::Concat(exprs)
+        }
+        HirKind::Capture(capture) => regexp_expr(&capture.sub)?,
+        HirKind::Concat(hirs) => Expr::Concat(hirs.iter().map(regexp_expr).collect::<Option<_>>()?),
+        HirKind::Alternation(hirs) => {
+            Expr::Alternation(hirs.iter().map(regexp_expr).collect::<Option<_>>()?)
+        }
+    })
+}
+
+/// Builds the expression for the value of a regexp group, or `None` if it
+/// uses a construct that isn't supported.
+pub(crate) fn regexp_group(value: &str, options: &Options) -> Option<Expr> {
+    let hir = ParserBuilder::new()
+        .case_insensitive(options.ignore_case)
+        .build()
+        .parse(value)
+        .ok()?;
+    regexp_expr(&hir)
+}
+
+/// Returns every code point that can appear in a URL component except `excluded`.
+fn negated(excluded: &[char]) -> CharClass {
+    let mut excluded: Vec<u32> = excluded
+        .iter()
+        .chain(&TAB_OR_NEWLINE)
+        .map(|&chr| chr as u32)
+        .collect();
+    excluded.sort_unstable();
+    excluded.dedup();
+
+    let mut class = vec![];
+    let mut start = 0;
+    for chr in excluded {
+        if chr > start {
+            class.push((start, chr - 1));
+        }
+        start = chr + 1;
+    }
+    if start <= MAX_CHAR {
+        class.push((start, MAX_CHAR));
+    }
+    class
+}
+
+/// Builds the expression that matches the same inputs as the regular
+/// expression generated for `part`.
+fn part_expr(part: &Part, options: &Options) -> Result<Expr, AnalysisError> {
+    let (modifier, regexp, prefix, suffix) = match part {
+        Part::FixedText { value, modifier } => {
+            return Ok(literal(value, options).with_modifier(*modifier));
+        }
+        Part::SegmentWildcard {
+            modifier,
+            prefix,
+            suffix,
+            ..
+        } => {
+            let excluded = options
+                .delimiter
+                .map_or(vec![], |chr| case_variants(chr, options.ignore_case));
+            let regexp = Expr::Class(negated(&excluded)).one_or_more();
+            (modifier, regexp, prefix, suffix)
+        }
+        Part::FullWildcard {
+            modifier,
+            prefix,
+            suffix,
+            ..
+        } => {
+            let regexp = Expr::Repeat(Box::new(Expr::Class(negated(&[]))));
+            (modifier, regexp, prefix, suffix)
+        }
+        Part::RegExp {
+            name,
+            value,
+            modifier,
+            prefix,
+            suffix,
+            ..
+        } => {
+            let regexp = regexp_group(value, options)
+                .ok_or_else(|| AnalysisError::UnsupportedRegExp(name.clone()))?;
+            (modifier, regexp, prefix, suffix)
+        }
+    };
+
+    if prefix.is_empty() && suffix.is_empty() {
+        return Ok(regexp.with_modifier(*modifier));
+    }
+
+    let prefix = literal(prefix, options);
+    let suffix = literal(suffix, options);
+    let repeated = || {
+        Expr::Concat(vec![
+            prefix.clone(),
+            regexp.clone(),
+            Expr::Repeat(Box::new(Expr::Concat(vec![
+                suffix.clone(),
+                prefix.clone(),
+                regexp.clone(),
+            ]))),
+            suffix.clone(),
+        ])
+    };
+
+    Ok(match modifier {
+        None => Expr::Concat(vec![prefix.clone(), regexp.clone(), suffix.clone()]),
+        Some(Modifier::Optional) => {
+            Expr::Concat(vec![prefix.clone(), regexp.clone(), suffix.clone()]).optional()
+        }
+        Some(Modifier::ZeroOrMore) => repeated().optional(),
+        Some(Modifier::OneOrMore) => repeated(),
+    })
+}
+
+#[derive(Debug, Default)]
+struct State {
+    epsilon: Vec<usize>,
+    transitions: Vec<(CharClass, usize)>,
+}
+
+/// A nondeterministic finite automaton with epsilon transitions.
+#[derive(Debug)]
+pub(crate) struct Nfa {
+    states: Vec<State>,
+    start: usize,
+    accept: usize,
+}
+
+impl Nfa {
+    pub(crate) fn new(parts: &[Part], options: &Options) -> Result<Nfa, AnalysisError> {
+        let expr = Expr::Concat(
+            parts
+                .iter()
+                .map(|part| part_expr(part, options))
+                .collect::<Result<_, _>>()?,
+        );
+
+        let mut nfa = Nfa {
+            states: vec![],
+            start: 0,
+            accept: 0,
+        };
+        let (start, accept) = nfa.compile(&expr);
+        nfa.start = start;
+        nfa.accept = accept;
+        Ok(nfa)
+    }
+
+    fn add_state(&mut self) -> usize {
+        self.states.push(State::default());
+        self.states.len() - 1
+    }
+
+    /// Thompson's construction, returns the start and accept state of `expr`.
+    fn compile(&mut self, expr: &Expr) -> (usize, usize) {
+        let start = self.add_state();
+        let accept = match expr {
+            Expr::Empty => start,
+            Expr::Class(class) => {
+                let accept = self.add_state();
+                self.states[start].transitions.push((class.clone(), accept));
+                accept
+            }
+            Expr::Concat(exprs) => {
+                let mut current = start;
+                for expr in exprs {
+                    let (inner_start, inner_accept) = self.compile(expr);
+                    self.states[current].epsilon.push(inner_start);
+                    current = inner_accept;
+                }
+                current
+            }
+            Expr::Alternation(exprs) => {
+                let accept = self.add_state();
+                for expr in exprs {
+                    let (inner_start, inner_accept) = self.compile(expr);
+                    self.states[start].epsilon.push(inner_start);
+                    self.states[inner_accept].epsilon.push(accept);
+                }
+                accept
+            }
+            Expr::Repeat(expr) => {
+                let (inner_start, inner_accept) = self.compile(expr);
+                self.states[start].epsilon.push(inner_start);
+                self.states[inner_accept].epsilon.push(start);
+                start
+            }
+        };
+        (start, accept)
+    }
+
+    fn closure(&self, mut states: BTreeSet<usize>) -> BTreeSet<usize> {
+        let mut stack: Vec<usize> = states.iter().copied().collect();
+        while let Some(state) = stack.pop() {
+            for &next in &self.states[state].epsilon {
+                if states.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+        states
+    }
+}
+
+/// Picks a readable character from the inclusive range `start..=end`.
+pub(crate) fn representative(start: u32, end: u32) -> char {
+    ('a'..='z')
+        .chain('0'..='9')
+        .chain('A'..='Z')
+        .find(|&chr| (start..=end).contains(&(chr as u32)))
+        .or_else(|| (start..=end).find_map(char::from_u32))
+        .expect("range contains a character")
+}
+
+/// The states of every automaton after reading the same input.
+type ProductState = Vec<BTreeSet<usize>>;
+
+/// Searches the product of `automata` breadth-first for the shortest input
+/// for which `goal` holds, given whether each automaton accepts the input.
+pub(crate) fn search(automata: &[&Nfa], goal: impl Fn(&[bool]) -> bool) -> Option<String> {
+    let initial: ProductState = automata
+        .iter()
+        .map(|nfa| nfa.closure(BTreeSet::from([nfa.start])))
+        .collect();
+
+    // For every visited product state, the previous state and character.
+    let mut visited: HashMap<ProductState, Option<(ProductState, char)>> =
+        HashMap::from([(initial.clone(), None)]);
+    let mut queue = VecDeque::from([initial]);
+
+    while let Some(current) = queue.pop_front() {
+        let accepting: Vec<bool> = automata
+            .iter()
+            .zip(&current)
+            .map(|(nfa, states)| states.contains(&nfa.accept))
+            .collect();
+
+        if goal(&accepting) {
+            let mut input = vec![];
+            let mut state = &current;
+            while let Some(Some((previous, chr))) = visited.get(state) {
+                input.push(*chr);
+                state = previous;
+            }
+            return Some(input.into_iter().rev().collect());
+        }
+
+        // Split the alphabet into ranges on which every transition agrees.
+        let mut boundaries = BTreeSet::new();
+        for (nfa, states) in automata.iter().zip(&current) {
+            for &state in states {
+                for (class, _) in &nfa.states[state].transitions {
+                    for &(start, end) in class {
+                        boundaries.insert(start);
+                        boundaries.insert(end + 1);
+                    }
+                }
+            }
+        }
+
+        let boundaries: Vec<u32> = boundaries.into_iter().collect();
+        for window in boundaries.windows(2) {
+            let (start, end) = (window[0], window[1] - 1);
+
+            let next: ProductState = automata
+                .iter()
+                .zip(&current)
+                .map(|(nfa, states)| {
+                    let targets = states
+                        .iter()
+                        .flat_map(|&state| &nfa.states[state].transitions)
+                        .filter(|(class, _)| {
+                            class.iter().any(|&(low, high)| low <= start && end <= high)
+                        })
+                        .map(|&(_, target)| target)
+                        .collect();
+                    nfa.closure(targets)
+                })
+                .collect();
+
+            if next.iter().all(BTreeSet::is_empty) || visited.contains_key(&next) {
+                continue;
+            }
+
+            visited.insert(
+                next.clone(),
+                Some((current.clone(), representative(start, end))),
+            );
+            queue.push_back(next);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AnalysisError, Component, Options};
+
+    fn pathname(pattern: &str) -> Component {
+        Component::compile(pattern, &Options::pathname()).unwrap()
+    }
+
+    #[test]
+    fn subset() {
+        let subset = |a: &str, b: &str| pathname(a).is_subset_of(&pathname(b)).unwrap();
+
+        assert!(subset("/api/v1/users", "/api/*"));
+        assert!(subset("/api/:version/users", "/api/*"));
+        assert!(subset("/files/:name.json", "/files/:file"));
+        assert!(subset("/a/:b+", "/a/:b*"));
+        assert!(subset("/a{/b}?", "/a/*?"));
+        assert!(!subset("/api/*", "/api/:version"));
+        assert!(!subset("/a/:b*", "/a/:b+"));
+        assert!(!subset("/API/*", "/api/*"));
+
+        let insensitive = Component::compile(
+            "/api/*",
+            &Options {
+                ignore_case: true,
+                ..Options::pathname()
+            },
+        )
+        .unwrap();
+        assert!(pathname("/API/*").is_subset_of(&insensitive).unwrap());
+    }
+
+    #[test]
+    fn overlap() {
+        let overlaps = |a: &str, b: &str| pathname(a).overlaps(&pathname(b)).unwrap();
+
+        assert_eq!(
+            overlaps("/users/:id", "/users/new").as_deref(),
+            Some("/users/new")
+        );
+        assert_eq!(
+            overlaps("/files/:name.json", "/files/report.*").as_deref(),
+            Some("/files/report.json")
+        );
+        assert_eq!(overlaps("/:a/b", "/a/:b").as_deref(), Some("/a/b"));
+        assert_eq!(overlaps("/users/:id", "/users/:id/edit"), None);
+        assert_eq!(overlaps("/a/:b+", "/a"), None);
+    }
+
+    #[test]
+    fn regexp_groups() {
+        let component = pathname(r"/users/:id(\d+)");
+        assert!(component.is_subset_of(&pathname("/users/:id")).unwrap());
+        assert!(component
+            .is_equivalent(&pathname(r"/users/(\d{1,})"))
+            .unwrap());
+        assert!(!component.is_equivalent(&pathname("/users/:id")).unwrap());
+        assert_eq!(
+            component
+                .overlaps(&pathname("/users/:name(me|[0-9a-f]+)"))
+                .unwrap()
+                .as_deref(),
+            Some("/users/0")
+        );
+
+        assert!(!component.is_empty().unwrap());
+        assert!(pathname(r"/a/([^\s\S])").is_empty().unwrap());
+        assert!(pathname("/a/(x\ny)").is_empty().unwrap());
+
+        assert_eq!(
+            pathname(r"/:word(\b\w+)").is_empty(),
+            Err(AnalysisError::UnsupportedRegExp("word".into()))
+        );
+    }
+}
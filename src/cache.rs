@@ -0,0 +1,502 @@
+// SPDX-License-Identifier: MIT
+
+//! A binary format for compiled components and URL patterns, so they don't
+//! have to be parsed again at every start.
+//!
+//! The format stores the options, the part list, the group names and the
+//! generated regular expression of every component. Loading checks the
+//! version tag and a checksum, and that the stored regular expression and
+//! group names are the ones the part list generates, so a file written by
+//! another version of the crate is rejected instead of misbehaving. The
+//! regular expressions of loaded components are only compiled when they are
+//! first matched against.
+//!
+//! Validation functions of constraints aren't stored, only the constraint
+//! names of groups. The functions are attached again from the
+//! [`Constraints`] passed when loading, which have to contain every
+//! constraint that is used.
+
+use thiserror::Error;
+
+use crate::component::Component;
+use crate::{Constraints, Modifier, Options, ParseError, Part, UrlPattern};
+
+const MAGIC: &[u8; 8] = b"URLPATC\0";
+
+/// The version of the format, written after the magic bytes. Changed whenever
+/// the format or the generated regular expressions change.
+pub const CACHE_FORMAT_VERSION: u32 = 2;
+
+#[derive(Error, Debug)]
+pub enum CacheError {
+    #[error("not a compiled pattern cache")]
+    Magic,
+    #[error("cache format version {found} isn't supported, expected {CACHE_FORMAT_VERSION}")]
+    Version { found: u32 },
+    #[error("the cache holds {found} but {expected} were requested")]
+    Kind {
+        found: &'static str,
+        expected: &'static str,
+    },
+    #[error("the checksum doesn't match, the cache is corrupted")]
+    Checksum,
+    #[error("unexpected end of the cache")]
+    Truncated,
+    #[error("invalid data in the cache: {0}")]
+    Invalid(&'static str),
+    #[error("the stored regular expression of `{0}` doesn't match its parts")]
+    Mismatch(String),
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+}
+
+const COMPONENTS: u8 = 0;
+const URL_PATTERNS: u8 = 1;
+
+fn kind_name(kind: u8) -> &'static str {
+    match kind {
+        COMPONENTS => "components",
+        URL_PATTERNS => "URL patterns",
+        _ => "unknown data",
+    }
+}
+
+/// 64-bit FNV-1a.
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn new(kind: u8, count: usize) -> Writer {
+        let mut writer = Writer { bytes: vec![] };
+        writer.bytes.extend_from_slice(MAGIC);
+        writer.u32(CACHE_FORMAT_VERSION);
+        writer.u8(kind);
+        writer.len(count);
+        writer
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn len(&mut self, len: usize) {
+        self.u32(u32::try_from(len).expect("fewer than 2^32 items"));
+    }
+
+    fn str(&mut self, value: &str) {
+        self.len(value.len());
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+    fn char(&mut self, value: Option<char>) {
+        self.u32(value.map_or(u32::MAX, u32::from));
+    }
+
+    fn optional_str(&mut self, value: Option<&str>) {
+        match value {
+            None => self.u8(0),
+            Some(value) => {
+                self.u8(1);
+                self.str(value);
+            }
+        }
+    }
+
+    fn component(&mut self, component: &Component) {
+        let options = component.options();
+        self.char(options.delimiter);
+        self.char(options.prefix);
+        self.u8(options.ignore_case.into());
+
+        self.len(component.parts().len());
+        for part in component.parts() {
+            let (tag, modifier) = match part {
+                Part::FixedText { modifier, .. } => (0, modifier),
+                Part::RegExp { modifier, .. } => (1, modifier),
+                Part::SegmentWildcard { modifier, .. } => (2, modifier),
+                Part::FullWildcard { modifier, .. } => (3, modifier),
+            };
+            self.u8(tag);
+            self.u8(match modifier {
+                None => 0,
+                Some(Modifier::Optional) => 1,
+                Some(Modifier::ZeroOrMore) => 2,
+                Some(Modifier::OneOrMore) => 3,
+            });
+            match part {
+                Part::FixedText { value, .. } => self.str(value),
+                Part::RegExp {
+                    name,
+                    value,
+                    prefix,
+                    suffix,
+                    constraint,
+                    ..
+                } => {
+                    self.str(name);
+                    self.str(value);
+                    self.str(prefix);
+                    self.str(suffix);
+                    self.optional_str(constraint.as_deref());
+                }
+                Part::SegmentWildcard {
+                    name,
+                    prefix,
+                    suffix,
+                    ..
+                }
+                | Part::FullWildcard {
+                    name,
+                    prefix,
+                    suffix,
+                    ..
+                } => {
+                    self.str(name);
+                    self.str(prefix);
+                    self.str(suffix);
+                }
+            }
+        }
+
+        self.len(component.group_names().len());
+        for name in component.group_names() {
+            self.str(name);
+        }
+        self.str(component.regexp());
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let checksum = checksum(&self.bytes);
+        self.bytes.extend_from_slice(&checksum.to_le_bytes());
+        self.bytes
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    constraints: &'a Constraints,
+}
+
+impl<'a> Reader<'a> {
+    /// Checks the header and the checksum and returns the number of items.
+    fn new(
+        bytes: &'a [u8],
+        kind: u8,
+        constraints: &'a Constraints,
+    ) -> Result<(Reader<'a>, usize), CacheError> {
+        if !bytes.starts_with(MAGIC) {
+            return Err(CacheError::Magic);
+        }
+        let mut reader = Reader {
+            bytes: &bytes[MAGIC.len()..],
+            constraints,
+        };
+        let found = reader.u32()?;
+        if found != CACHE_FORMAT_VERSION {
+            return Err(CacheError::Version { found });
+        }
+
+        let (content, stored) = bytes.split_last_chunk::<8>().ok_or(CacheError::Truncated)?;
+        if content.len() < MAGIC.len() + 4 || checksum(content) != u64::from_le_bytes(*stored) {
+            return Err(CacheError::Checksum);
+        }
+        reader.bytes = &content[MAGIC.len() + 4..];
+
+        let found = reader.u8()?;
+        if found != kind {
+            return Err(CacheError::Kind {
+                found: kind_name(found),
+                expected: kind_name(kind),
+            });
+        }
+        let count = reader.len()?;
+        Ok((reader, count))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CacheError> {
+        if self.bytes.len() < len {
+            return Err(CacheError::Truncated);
+        }
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, CacheError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, CacheError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().expect("four bytes")))
+    }
+
+    fn len(&mut self) -> Result<usize, CacheError> {
+        let len = self.u32()? as usize;
+        // Every item takes at least one byte, so this bounds allocations by
+        // the size of the input.
+        if len > self.bytes.len() {
+            return Err(CacheError::Truncated);
+        }
+        Ok(len)
+    }
+
+    fn str(&mut self) -> Result<String, CacheError> {
+        let len = self.len()?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| CacheError::Invalid("invalid UTF-8"))
+    }
+
+    fn char(&mut self) -> Result<Option<char>, CacheError> {
+        match self.u32()? {
+            u32::MAX => Ok(None),
+            value => char::from_u32(value)
+                .map(Some)
+                .ok_or(CacheError::Invalid("invalid character")),
+        }
+    }
+
+    fn optional_str(&mut self) -> Result<Option<String>, CacheError> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => self.str().map(Some),
+            _ => Err(CacheError::Invalid("invalid optional string")),
+        }
+    }
+
+    fn bool(&mut self) -> Result<bool, CacheError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(CacheError::Invalid("invalid boolean")),
+        }
+    }
+
+    fn component(&mut self) -> Result<Component, CacheError> {
+        let options = Options {
+            delimiter: self.char()?,
+            prefix: self.char()?,
+            ignore_case: self.bool()?,
+            constraints: self.constraints.clone(),
+        };
+
+        let count = self.len()?;
+        let mut parts = Vec::with_capacity(count);
+        for _ in 0..count {
+            let tag = self.u8()?;
+            let modifier = match self.u8()? {
+                0 => None,
+                1 => Some(Modifier::Optional),
+                2 => Some(Modifier::ZeroOrMore),
+                3 => Some(Modifier::OneOrMore),
+                _ => return Err(CacheError::Invalid("invalid modifier")),
+            };
+            parts.push(match tag {
+                0 => Part::FixedText {
+                    value: self.str()?,
+                    modifier,
+                },
+                1 => Part::RegExp {
+                    name: self.str()?,
+                    value: self.str()?,
+                    modifier,
+                    prefix: self.str()?,
+                    suffix: self.str()?,
+                    constraint: self.optional_str()?,
+                },
+                2 => Part::SegmentWildcard {
+                    name: self.str()?,
+                    modifier,
+                    prefix: self.str()?,
+                    suffix: self.str()?,
+                },
+                3 => Part::FullWildcard {
+                    name: self.str()?,
+                    modifier,
+                    prefix: self.str()?,
+                    suffix: self.str()?,
+                },
+                _ => return Err(CacheError::Invalid("invalid part type")),
+            });
+        }
+
+        let count = self.len()?;
+        let group_names = (0..count)
+            .map(|_| self.str())
+            .collect::<Result<Vec<_>, _>>()?;
+        let regexp = self.str()?;
+
+        let component = Component::from_parts_lazy(parts, &options)?;
+        if component.regexp() != regexp || component.group_names() != group_names {
+            return Err(CacheError::Mismatch(component.pattern_string().into()));
+        }
+        Ok(component)
+    }
+
+    fn finish<T>(self, items: T) -> Result<T, CacheError> {
+        if !self.bytes.is_empty() {
+            return Err(CacheError::Invalid("trailing data"));
+        }
+        Ok(items)
+    }
+}
+
+/// Writes compiled components in the cache format.
+pub fn write_components<'a>(components: impl ExactSizeIterator<Item = &'a Component>) -> Vec<u8> {
+    let mut writer = Writer::new(COMPONENTS, components.len());
+    for component in components {
+        writer.component(component);
+    }
+    writer.finish()
+}
+
+/// Loads components written by [`write_components`].
+pub fn read_components(
+    bytes: &[u8],
+    constraints: &Constraints,
+) -> Result<Vec<Component>, CacheError> {
+    let (mut reader, count) = Reader::new(bytes, COMPONENTS, constraints)?;
+    let components = (0..count)
+        .map(|_| reader.component())
+        .collect::<Result<Vec<_>, _>>()?;
+    reader.finish(components)
+}
+
+/// Writes URL patterns in the cache format.
+pub fn write_url_patterns<'a>(patterns: impl ExactSizeIterator<Item = &'a UrlPattern>) -> Vec<u8> {
+    let mut writer = Writer::new(URL_PATTERNS, patterns.len());
+    for pattern in patterns {
+        for component in pattern.components() {
+            writer.component(component);
+        }
+    }
+    writer.finish()
+}
+
+/// Loads URL patterns written by [`write_url_patterns`].
+pub fn read_url_patterns(
+    bytes: &[u8],
+    constraints: &Constraints,
+) -> Result<Vec<UrlPattern>, CacheError> {
+    let (mut reader, count) = Reader::new(bytes, URL_PATTERNS, constraints)?;
+    let mut patterns = Vec::with_capacity(count);
+    for _ in 0..count {
+        let components = [(); 8].map(|_| reader.component());
+        let [protocol, username, password, hostname, port, pathname, search, hash] = components;
+        patterns.push(UrlPattern::from_components([
+            protocol?, username?, password?, hostname?, port?, pathname?, search?, hash?,
+        ]));
+    }
+    reader.finish(patterns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UrlPatternOptions;
+
+    #[test]
+    fn round_trip() {
+        let patterns = [
+            "https://:tenant.example.com/users/:id(\\d+)",
+            "http{s}?://*.example.org/static/*",
+        ]
+        .map(|input| UrlPattern::parse(input, None, &UrlPatternOptions::default()).unwrap());
+        let bytes = write_url_patterns(patterns.iter());
+
+        let loaded = read_url_patterns(&bytes, &Constraints::new()).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].to_init(), patterns[0].to_init());
+        let result = loaded[0]
+            .exec_url("https://acme.example.com/users/42")
+            .unwrap();
+        assert_eq!(result.hostname.groups["tenant"].as_deref(), Some("acme"));
+        assert!(loaded[1].test_url("http://cdn.example.org/static/app.js"));
+
+        assert!(matches!(
+            read_components(&bytes, &Constraints::new()),
+            Err(CacheError::Kind { .. })
+        ));
+
+        let mut corrupted = bytes.clone();
+        corrupted[20] ^= 1;
+        assert!(matches!(
+            read_url_patterns(&corrupted, &Constraints::new()),
+            Err(CacheError::Checksum)
+        ));
+
+        let mut other_version = bytes.clone();
+        other_version[8] = 0;
+        assert!(matches!(
+            read_url_patterns(&other_version, &Constraints::new()),
+            Err(CacheError::Version { found: 0 })
+        ));
+        assert!(matches!(
+            read_url_patterns(&bytes[..bytes.len() / 2], &Constraints::new()),
+            Err(CacheError::Checksum)
+        ));
+    }
+
+    #[test]
+    fn constraints() {
+        let options = Options {
+            constraints: Constraints::builtin(),
+            ..Options::pathname()
+        };
+        let component = Component::compile("/users/:id<int>", &options).unwrap();
+        let bytes = write_components([&component].into_iter());
+
+        let loaded = read_components(&bytes, &Constraints::builtin()).unwrap();
+        assert!(loaded[0].test("/users/42"));
+        assert!(!loaded[0].test("/users/99999999999999999999"));
+    }
+
+    /// Replaces `from` with `to` of the same length everywhere in a cache and
+    /// updates its checksum.
+    fn tamper(bytes: &[u8], from: &[u8], to: &[u8]) -> Vec<u8> {
+        let mut content = bytes[..bytes.len() - 8].to_vec();
+        for start in 0..content.len() - from.len() {
+            if &content[start..start + from.len()] == from {
+                content[start..start + to.len()].copy_from_slice(to);
+            }
+        }
+        let checksum = checksum(&content);
+        content.extend_from_slice(&checksum.to_le_bytes());
+        content
+    }
+
+    #[test]
+    fn invalid_regexps() {
+        let component = Component::compile(r"/users/:id(\d+)", &Options::pathname()).unwrap();
+        let bytes = write_components([&component].into_iter());
+
+        // The stored regular expression matches the tampered parts, but the
+        // parts are still checked like a pattern string.
+        let capturing = tamper(&bytes, br"\d+", b"(a)");
+        assert!(matches!(
+            read_components(&capturing, &Constraints::new()),
+            Err(CacheError::Parse(ParseError::CapturingGroupInRegExp))
+        ));
+        let invalid = tamper(&bytes, br"\d+", b"[a+");
+        assert!(matches!(
+            read_components(&invalid, &Constraints::new()),
+            Err(CacheError::Parse(ParseError::InvalidRegExp(_)))
+        ));
+        let unbalanced = tamper(&bytes, br"\d+", b"a)(");
+        assert!(matches!(
+            read_components(&unbalanced, &Constraints::new()),
+            Err(CacheError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,334 @@
+// SPDX-License-Identifier: MIT
+
+use crate::automaton::{search, Nfa};
+use crate::constraint::Validate;
+use crate::examples::{generate, Rng};
+use crate::explain::{explain, prefix_regexps};
+use crate::tokenizer::{tokenize, Policy, TokenKind};
+use crate::{
+    generate_pattern_string, generate_regexp, parse_pattern, AnalysisError, DialectError,
+    Explanation, ExtractError, FromUrlPattern, Options, ParseError, Part, RegexDialect,
+};
+
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// A pattern string compiled for matching a single URL component.
+///
+/// <https://urlpattern.spec.whatwg.org/#component>
+#[derive(Debug, Clone)]
+pub struct Component {
+    options: Options,
+    pattern_string: String,
+    parts: Vec<Part>,
+    regexp_string: String,
+    /// Compiled on first use for components loaded from a cache.
+    regexp: OnceLock<Regex>,
+    group_names: Vec<String>,
+    /// The validation functions of constraints, by capture group index.
+    validators: Vec<(usize, Validate)>,
+    /// The regular expressions of every prefix of the parts, compiled on
+    /// first use by [`Component::explain`].
+    prefix_regexps: OnceLock<Vec<Regex>>,
+}
+
+/// Checks the value of a regexp part like the tokenizer checks a regexp group
+/// of a pattern string, so it can't contain capturing groups.
+fn check_regexp_value(value: &str) -> Result<(), ParseError> {
+    let group = format!("({value})");
+    let tokens = tokenize(&group, Policy::Strict, false)?;
+    match tokens[0].kind {
+        TokenKind::RegExp(regexp) if tokens.len() == 2 && regexp == value => Ok(()),
+        _ => Err(ParseError::ParenthesesMissmatch),
+    }
+}
+
+/// <https://urlpattern.spec.whatwg.org/#dictdef-urlpatterncomponentresult>
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ComponentResult {
+    pub input: String,
+    pub groups: HashMap<String, Option<String>>,
+}
+
+impl Component {
+    /// <https://urlpattern.spec.whatwg.org/#compile-a-component>
+    pub fn compile(input: &str, options: &Options) -> Result<Component, ParseError> {
+        // Let part list be the result of running parse a pattern string given input, options, and encoding callback.
+        let parts = parse_pattern(input, options)?;
+
+        Component::from_parts(parts, options)
+    }
+
+    /// Compiles a part list that was produced by something other than the
+    /// pattern string parser.
+    pub fn from_parts(parts: Vec<Part>, options: &Options) -> Result<Component, ParseError> {
+        let component = Component::from_parts_lazy(parts, options)?;
+
+        // Let regular expression be RegExpCreate(regular expression string, flags).
+        let regexp = Regex::new(&component.regexp_string)?;
+        component
+            .regexp
+            .set(regexp)
+            .expect("the regular expression isn't compiled yet");
+        Ok(component)
+    }
+
+    /// Like [`Component::from_parts`], but compiles the regular expression
+    /// only when the component is first matched against.
+    ///
+    /// The values of regexp parts and the syntax of the regular expression
+    /// are still checked, so that matching can't fail later.
+    pub(crate) fn from_parts_lazy(
+        parts: Vec<Part>,
+        options: &Options,
+    ) -> Result<Component, ParseError> {
+        let mut names = HashSet::new();
+        for part in &parts {
+            if let Part::SegmentWildcard { name, .. }
+            | Part::FullWildcard { name, .. }
+            | Part::RegExp { name, .. } = part
+            {
+                if !names.insert(name) {
+                    return Err(ParseError::DuplicateName(name.clone()));
+                }
+            }
+            if let Part::RegExp { value, .. } = part {
+                check_regexp_value(value)?;
+            }
+        }
+
+        // Let (regular expression string, name list) be the result of running generate a regular expression and name list given part list and options.
+        let (regexp_string, group_names, _) = generate_regexp(&parts, options, RegexDialect::Rust)
+            .expect("Rust regex supports every pattern");
+        regex_syntax::Parser::new()
+            .parse(&regexp_string)
+            .map_err(|error| regex::Error::Syntax(error.to_string()))?;
+
+        // Let pattern string be the result of running generate a pattern string given part list and options.
+        let pattern_string = generate_pattern_string(&parts, options);
+
+        let mut validators = vec![];
+        let groups = parts
+            .iter()
+            .filter(|part| !matches!(part, Part::FixedText { .. }));
+        for (index, part) in groups.enumerate() {
+            if let Part::RegExp {
+                constraint: Some(constraint),
+                ..
+            } = part
+            {
+                let validate = options
+                    .constraints
+                    .get(constraint)
+                    .ok_or_else(|| ParseError::UnknownConstraint(constraint.clone()))?
+                    .validate;
+                validators.extend(validate.map(|validate| (index + 1, validate)));
+            }
+        }
+
+        Ok(Component {
+            options: options.clone(),
+            pattern_string,
+            parts,
+            regexp_string,
+            regexp: OnceLock::new(),
+            group_names,
+            validators,
+            prefix_regexps: OnceLock::new(),
+        })
+    }
+
+    /// The options the component was compiled with.
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// The normalized pattern string.
+    pub fn pattern_string(&self) -> &str {
+        &self.pattern_string
+    }
+
+    /// The parts the pattern string was parsed into.
+    pub fn parts(&self) -> &[Part] {
+        &self.parts
+    }
+
+    /// The generated regular expression, in the syntax of the Rust `regex` crate.
+    pub fn regexp(&self) -> &str {
+        &self.regexp_string
+    }
+
+    /// Generates the regular expression for this component in another syntax.
+    ///
+    /// The capture groups are in the same order as [`Component::group_names`],
+    /// but [`RegexDialect::PosixExtended`] has more of them, see
+    /// [`Component::group_indices_for`]. The values of regexp groups are copied
+    /// verbatim, so they have to be valid in `dialect` as well.
+    pub fn regexp_for(&self, dialect: RegexDialect) -> Result<String, DialectError> {
+        let (regexp, ..) = generate_regexp(&self.parts, &self.options, dialect)?;
+        Ok(regexp)
+    }
+
+    /// The index of the capture group of every name in [`Component::group_names`]
+    /// in the regular expression [`Component::regexp_for`] returns.
+    pub fn group_indices_for(&self, dialect: RegexDialect) -> Result<Vec<usize>, DialectError> {
+        let (.., indices) = generate_regexp(&self.parts, &self.options, dialect)?;
+        Ok(indices)
+    }
+
+    /// The names of the groups in the order of their capture groups in [`Component::regexp`].
+    pub fn group_names(&self) -> &[String] {
+        &self.group_names
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#component-has-regexp-groups>
+    pub fn has_regexp_groups(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|part| matches!(part, Part::RegExp { .. }))
+    }
+
+    /// Returns whether `input` matches this component.
+    pub fn test(&self, input: &str) -> bool {
+        if self.validators.is_empty() {
+            return self.compiled_regexp().is_match(input);
+        }
+        self.captures(input).is_some()
+    }
+
+    fn compiled_regexp(&self) -> &Regex {
+        self.regexp.get_or_init(|| {
+            Regex::new(&self.regexp_string).expect("the regular expression was checked before")
+        })
+    }
+
+    /// Matches `input` and runs the validation functions of constraints.
+    fn captures<'a>(&self, input: &'a str) -> Option<regex::Captures<'a>> {
+        let captures = self.compiled_regexp().captures(input)?;
+        self.validators
+            .iter()
+            .all(|&(index, validate)| captures.get(index).is_none_or(|m| validate(m.as_str())))
+            .then_some(captures)
+    }
+
+    /// Explains which part rejected `input`, or returns `None` if it matches.
+    pub fn explain(&self, input: &str) -> Option<Explanation> {
+        let regexps = self
+            .prefix_regexps
+            .get_or_init(|| prefix_regexps(&self.parts, &self.options));
+        explain(&self.parts, &self.options, &self.validators, regexps, input)
+    }
+
+    /// Matches `input` against this component and returns the captured groups.
+    pub fn exec(&self, input: &str) -> Option<ComponentResult> {
+        let captures = self.captures(input)?;
+
+        // https://urlpattern.spec.whatwg.org/#create-a-component-match-result
+        let groups = self
+            .group_names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let value = captures.get(index + 1).map(|m| m.as_str().to_string());
+                (name.clone(), value)
+            })
+            .collect();
+
+        Some(ComponentResult {
+            input: input.to_string(),
+            groups,
+        })
+    }
+
+    /// Matches `input` against this component and fills `T` from the groups.
+    pub fn extract<T: FromUrlPattern>(&self, input: &str) -> Option<Result<T, ExtractError>> {
+        let result = self.exec(input)?;
+        Some(T::from_result(&result, self))
+    }
+
+    /// Builds the automaton for the analysis methods.
+    fn nfa(&self) -> Result<Nfa, AnalysisError> {
+        if let Some(&(index, _)) = self.validators.first() {
+            let name = &self.group_names[index - 1];
+            return Err(AnalysisError::Validated(name.clone()));
+        }
+        Nfa::new(&self.parts, &self.options)
+    }
+
+    /// Returns whether every input matched by this component is also matched
+    /// by `other`.
+    ///
+    /// Components with assertions like `^` or `\b` in regexp groups, or with
+    /// constraints that have a validation function, can't be analyzed.
+    pub fn is_subset_of(&self, other: &Component) -> Result<bool, AnalysisError> {
+        let this = self.nfa()?;
+        let other = other.nfa()?;
+        Ok(search(&[&this, &other], |accepting| accepting[0] && !accepting[1]).is_none())
+    }
+
+    /// Returns an input that is matched by both this component and `other`,
+    /// or `None` if they don't overlap.
+    ///
+    /// The input is one of the shortest inputs matched by both.
+    pub fn overlaps(&self, other: &Component) -> Result<Option<String>, AnalysisError> {
+        let this = self.nfa()?;
+        let other = other.nfa()?;
+        Ok(search(&[&this, &other], |accepting| {
+            accepting[0] && accepting[1]
+        }))
+    }
+
+    /// Returns whether this component and `other` match exactly the same inputs.
+    pub fn is_equivalent(&self, other: &Component) -> Result<bool, AnalysisError> {
+        let this = self.nfa()?;
+        let other = other.nfa()?;
+        Ok(search(&[&this, &other], |accepting| accepting[0] != accepting[1]).is_none())
+    }
+
+    /// Returns whether this component can never match any input.
+    pub fn is_empty(&self) -> Result<bool, AnalysisError> {
+        let this = self.nfa()?;
+        Ok(search(&[&this], |accepting| accepting[0]).is_none())
+    }
+
+    /// Generates up to `n` distinct inputs that match this component.
+    ///
+    /// The examples alternate between a part being present once, absent and
+    /// repeated, as far as its modifier allows. Fewer examples are returned if
+    /// the component doesn't match enough distinct inputs, or if they are too
+    /// hard to find, for example because of a regexp group with assertions.
+    pub fn examples(&self, n: usize) -> Vec<String> {
+        let mut examples: Vec<String> = vec![];
+
+        for index in 0..n * 8 + 8 {
+            if examples.len() == n {
+                break;
+            }
+            let input = generate(
+                &self.parts,
+                &self.options,
+                index,
+                &mut Rng::new(index as u64),
+            );
+            if self.test(&input) && !examples.contains(&input) {
+                examples.push(input);
+            }
+        }
+
+        examples
+    }
+
+    /// A [`proptest`] strategy for inputs that match this component.
+    ///
+    /// The inputs are generated from the parts of the component, so every
+    /// input matches. Fails for regexp groups that proptest can't generate
+    /// values for, like groups with assertions.
+    #[cfg(feature = "proptest")]
+    pub fn input_strategy(
+        &self,
+    ) -> Result<impl proptest::strategy::Strategy<Value = String>, AnalysisError> {
+        crate::examples::strategy(&self.parts, &self.options, &self.validators)
+    }
+}
@@ -0,0 +1,217 @@
+// SPDX-License-Identifier: MIT
+
+//! Named constraint types for groups, like `:id<int>`.
+//!
+//! A constraint is a regexp fragment with a name, and optionally a function
+//! that validates the matched value. `:id<int>` is parsed into a regexp part
+//! with the fragment of `int` as its value and `int` as its constraint, and
+//! only such parts run the validation function. `:id([0-9]+)` doesn't.
+//!
+//! The analysis methods of [`Component`](crate::Component) can't see into
+//! validation functions, and fail for components that have any.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+
+use crate::tokenizer::{tokenize, Policy, TokenKind};
+use crate::ParseError;
+
+/// A function that validates the value a constraint matched.
+pub type Validate = fn(&str) -> bool;
+
+/// A regexp fragment and an optional validation function for its matches.
+#[derive(Clone, Debug)]
+pub struct Constraint {
+    pub regexp: String,
+    pub validate: Option<Validate>,
+}
+
+/// Maps constraint names to [`Constraint`]s.
+///
+/// Without any registered constraint, a `<` after a name is fixed text, like
+/// the spec requires.
+#[derive(Clone, Debug, Default)]
+pub struct Constraints {
+    constraints: Arc<BTreeMap<String, Constraint>>,
+}
+
+impl Constraints {
+    pub fn new() -> Constraints {
+        Constraints::default()
+    }
+
+    /// The `int`, `slug` and `uuid` constraints.
+    ///
+    /// `int` only accepts values that fit into a `u64`.
+    pub fn builtin() -> Constraints {
+        let mut constraints = Constraints::new();
+        constraints
+            .insert("int", "[0-9]+", Some(|value| value.parse::<u64>().is_ok()))
+            .expect("valid constraint");
+        constraints
+            .insert("slug", "[a-z0-9]+(?:-[a-z0-9]+)*", None)
+            .expect("valid constraint");
+        constraints
+            .insert(
+                "uuid",
+                "[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}",
+                None,
+            )
+            .expect("valid constraint");
+        constraints
+    }
+
+    /// Registers a constraint, replacing any previous constraint with the
+    /// same name.
+    ///
+    /// `regexp` has to be valid as the value of a regexp group, so it can't
+    /// contain capturing groups.
+    pub fn insert(
+        &mut self,
+        name: &str,
+        regexp: &str,
+        validate: Option<Validate>,
+    ) -> Result<(), ParseError> {
+        let group = format!("({regexp})");
+        let tokens = tokenize(&group, Policy::Strict, false)?;
+        if tokens.len() != 2 || !matches!(tokens[0].kind, TokenKind::RegExp(_)) {
+            return Err(ParseError::InvalidConstraint(regexp.into()));
+        }
+        #[cfg(feature = "regex")]
+        regex::Regex::new(regexp)?;
+
+        Arc::make_mut(&mut self.constraints).insert(
+            name.into(),
+            Constraint {
+                regexp: regexp.into(),
+                validate,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Constraint> {
+        self.constraints.get(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.constraints.is_empty()
+    }
+}
+
+#[cfg(all(test, feature = "regex"))]
+mod tests {
+    use crate::{AnalysisError, Component, Constraints, Options, ParseError, Part};
+
+    #[test]
+    fn constraints() {
+        let options = Options {
+            constraints: Constraints::builtin(),
+            ..Options::pathname()
+        };
+        let component = Component::compile("/users/:id<int>/:slug<slug>?", &options).unwrap();
+        assert_eq!(component.pattern_string(), "/users/:id<int>/:slug<slug>?");
+        let round_trip = Component::compile(component.pattern_string(), &options).unwrap();
+        assert_eq!(round_trip.parts(), component.parts());
+        assert_eq!(
+            component.regexp(),
+            r"^/users(?:/([0-9]+))(?:/([a-z0-9]+(?:-[a-z0-9]+)*))?$"
+        );
+        let result = component.exec("/users/42/hello-world").unwrap();
+        assert_eq!(result.groups["id"].as_deref(), Some("42"));
+        assert!(!component.test("/users/x/hello-world"));
+        assert!(!component.test("/users/99999999999999999999/hello-world"));
+
+        let uuid = Component::compile("/:uuid<uuid>", &options).unwrap();
+        assert!(uuid.test("/67e55044-10b1-426f-9247-bb680e5fe0c8"));
+        assert!(!uuid.test("/67e55044"));
+
+        assert!(matches!(
+            Component::compile("/:id<float>", &options),
+            Err(ParseError::UnknownConstraint(name)) if name == "float"
+        ));
+        assert!(matches!(
+            Component::compile("/:id<int", &options),
+            Err(ParseError::UnclosedConstraint)
+        ));
+
+        // Without a registry `<` is fixed text.
+        let component = Component::compile("/:id<int>", &Options::pathname()).unwrap();
+        assert!(component.test("/42<int>"));
+        assert_eq!(component.pattern_string(), "/:id<int>");
+
+        // With one, a `<` after a name has to be escaped or grouped to be fixed text.
+        for (pattern, pattern_string, input) in [
+            (r"/:id\<x", "{/:id}<x", "/5<x"),
+            ("{/:id}<x", "{/:id}<x", "/5<x"),
+            (r"/{a:id\<x}?", r"/{a:id\<x}?", "/a5<x"),
+            ("/a<b>", "/a<b>", "/a<b>"),
+        ] {
+            let component = Component::compile(pattern, &options).unwrap();
+            assert!(component.test(input), "{pattern}");
+            assert_eq!(component.pattern_string(), pattern_string);
+            let round_trip = Component::compile(pattern_string, &options).unwrap();
+            assert_eq!(round_trip.parts(), component.parts());
+        }
+
+        let mut constraints = Constraints::new();
+        assert!(constraints.insert("bad", "(a)", None).is_err());
+    }
+
+    #[test]
+    fn validators() {
+        let mut constraints = Constraints::builtin();
+        constraints
+            .insert(
+                "even",
+                "[0-9]+",
+                Some(|value| value.ends_with(['0', '2', '4', '6', '8'])),
+            )
+            .unwrap();
+        let options = Options {
+            constraints,
+            ..Options::pathname()
+        };
+
+        // Only groups written with a constraint run its validation function.
+        let component = Component::compile("/:n([0-9]+)", &options).unwrap();
+        assert!(component.test("/99999999999999999999"));
+        assert!(matches!(
+            &component.parts()[0],
+            Part::RegExp {
+                constraint: None,
+                ..
+            }
+        ));
+
+        // Constraints with the same fragment keep their own functions.
+        let component = Component::compile("/:a<int>/:b<even>", &options).unwrap();
+        assert!(component.test("/1/4"));
+        assert!(!component.test("/1/3"));
+        assert!(!component.test("/99999999999999999999/4"));
+
+        // The analysis can't see into validation functions.
+        let plain = Component::compile("/:a([0-9]+)/:b([0-9]+)", &options).unwrap();
+        assert!(matches!(
+            component.is_equivalent(&plain),
+            Err(AnalysisError::Validated(name)) if name == "a"
+        ));
+        assert!(matches!(
+            plain.is_subset_of(&component),
+            Err(AnalysisError::Validated(_))
+        ));
+        assert!(component.is_empty().is_err());
+        assert!(Component::compile("/:s<slug>", &options)
+            .unwrap()
+            .is_empty()
+            .is_ok());
+
+        let explanation = component.explain("/1/3").unwrap();
+        assert_eq!(explanation.matched, "/1");
+        assert_eq!(explanation.index, 1);
+        assert_eq!(explanation.actual, "/3");
+        assert_eq!(component.explain("/1/2"), None);
+    }
+}
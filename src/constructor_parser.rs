@@ -0,0 +1,387 @@
+// SPDX-License-Identifier: MIT
+
+use crate::tokenizer::{tokenize, Policy, Token, TokenKind};
+use crate::url_pattern::SPECIAL_SCHEMES;
+use crate::{Component, Options, ParseError, UrlPatternInit};
+
+/// <https://urlpattern.spec.whatwg.org/#constructor-string-parser-state>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum State {
+    Init,
+    Protocol,
+    Authority,
+    Username,
+    Password,
+    Hostname,
+    Port,
+    Pathname,
+    Search,
+    Hash,
+    Done,
+}
+
+/// <https://urlpattern.spec.whatwg.org/#constructor-string-parser>
+struct ConstructorStringParser<'a> {
+    input: &'a str,
+    tokens: Vec<Token<'a>>,
+    result: UrlPatternInit,
+    component_start: usize,
+    token_index: usize,
+    token_increment: usize,
+    group_depth: usize,
+    hostname_ipv6_bracket_depth: usize,
+    protocol_matches_special_scheme: bool,
+    state: State,
+}
+
+impl<'a> ConstructorStringParser<'a> {
+    /// <https://urlpattern.spec.whatwg.org/#parse-a-constructor-string>
+    fn parse(mut self) -> Result<UrlPatternInit, ParseError> {
+        // While parser’s token index is less than parser’s token list size:
+        while self.token_index < self.tokens.len() {
+            // Set parser’s token increment to 1.
+            self.token_increment = 1;
+
+            // If parser’s token list[parser’s token index]'s type is "end" then:
+            if matches!(self.tokens[self.token_index].kind, TokenKind::End) {
+                // If parser’s state is "init":
+                if self.state == State::Init {
+                    // Run rewind given parser.
+                    self.rewind();
+
+                    // If the result of running is a hash prefix given parser is true, then run change state given parser, "hash" and 1.
+                    if self.is_hash_prefix() {
+                        self.change_state(State::Hash, 1);
+                    // Otherwise if the result of running is a search prefix given parser is true:
+                    } else if self.is_search_prefix() {
+                        self.change_state(State::Search, 1);
+                    // Otherwise: Run change state given parser, "pathname" and 0.
+                    } else {
+                        self.change_state(State::Pathname, 0);
+                    }
+
+                    self.token_index += self.token_increment;
+                    continue;
+                }
+
+                // If parser’s state is "authority":
+                if self.state == State::Authority {
+                    // Run rewind and set state given parser, and "hostname".
+                    self.rewind_and_set_state(State::Hostname);
+                    self.token_index += self.token_increment;
+                    continue;
+                }
+
+                // Run change state given parser, "done" and 0.
+                self.change_state(State::Done, 0);
+                break;
+            }
+
+            // If the result of running is a group open given parser is true:
+            if self.is_group_open() {
+                self.group_depth += 1;
+                self.token_index += self.token_increment;
+                continue;
+            }
+
+            // If parser’s group depth is greater than 0:
+            if self.group_depth > 0 {
+                if self.is_group_close() {
+                    self.group_depth -= 1;
+                } else {
+                    self.token_index += self.token_increment;
+                    continue;
+                }
+            }
+
+            match self.state {
+                State::Init => {
+                    if self.is_protocol_suffix() {
+                        self.rewind_and_set_state(State::Protocol);
+                    }
+                }
+                State::Protocol => {
+                    if self.is_protocol_suffix() {
+                        // Run compute protocol matches a special scheme flag given parser.
+                        self.compute_protocol_matches_special_scheme()?;
+
+                        let mut next_state = State::Pathname;
+                        let mut skip = 1;
+                        if self.next_is_authority_slashes() {
+                            next_state = State::Authority;
+                            skip = 3;
+                        } else if self.protocol_matches_special_scheme {
+                            next_state = State::Authority;
+                        }
+                        self.change_state(next_state, skip);
+                    }
+                }
+                State::Authority => {
+                    if self.is_identity_terminator() {
+                        self.rewind_and_set_state(State::Username);
+                    } else if self.is_pathname_start()
+                        || self.is_search_prefix()
+                        || self.is_hash_prefix()
+                    {
+                        self.rewind_and_set_state(State::Hostname);
+                    }
+                }
+                State::Username => {
+                    if self.is_password_prefix() {
+                        self.change_state(State::Password, 1);
+                    } else if self.is_identity_terminator() {
+                        self.change_state(State::Hostname, 1);
+                    }
+                }
+                State::Password => {
+                    if self.is_identity_terminator() {
+                        self.change_state(State::Hostname, 1);
+                    }
+                }
+                State::Hostname => {
+                    if self.is_ipv6_open() {
+                        self.hostname_ipv6_bracket_depth += 1;
+                    } else if self.is_ipv6_close() {
+                        self.hostname_ipv6_bracket_depth =
+                            self.hostname_ipv6_bracket_depth.saturating_sub(1);
+                    } else if self.is_port_prefix() && self.hostname_ipv6_bracket_depth == 0 {
+                        self.change_state(State::Port, 1);
+                    } else if self.is_pathname_start() {
+                        self.change_state(State::Pathname, 0);
+                    } else if self.is_search_prefix() {
+                        self.change_state(State::Search, 1);
+                    } else if self.is_hash_prefix() {
+                        self.change_state(State::Hash, 1);
+                    }
+                }
+                State::Port => {
+                    if self.is_pathname_start() {
+                        self.change_state(State::Pathname, 0);
+                    } else if self.is_search_prefix() {
+                        self.change_state(State::Search, 1);
+                    } else if self.is_hash_prefix() {
+                        self.change_state(State::Hash, 1);
+                    }
+                }
+                State::Pathname => {
+                    if self.is_search_prefix() {
+                        self.change_state(State::Search, 1);
+                    } else if self.is_hash_prefix() {
+                        self.change_state(State::Hash, 1);
+                    }
+                }
+                State::Search => {
+                    if self.is_hash_prefix() {
+                        self.change_state(State::Hash, 1);
+                    }
+                }
+                State::Hash => {}
+                State::Done => unreachable!("the loop ends in the done state"),
+            }
+
+            self.token_index += self.token_increment;
+        }
+
+        // If parser’s result["hostname"] exists and parser’s result["port"] does not exist, then set parser’s result["port"] to the empty string.
+        if self.result.hostname.is_some() && self.result.port.is_none() {
+            self.result.port = Some(String::new());
+        }
+
+        Ok(self.result)
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#change-state>
+    fn change_state(&mut self, new_state: State, skip: usize) {
+        // If parser’s state is not "init", not "authority", and not "done", then set parser’s result[parser’s state] to the result of running make a component string given parser.
+        let component = self.make_component_string();
+        match self.state {
+            State::Protocol => self.result.protocol = Some(component),
+            State::Username => self.result.username = Some(component),
+            State::Password => self.result.password = Some(component),
+            State::Hostname => self.result.hostname = Some(component),
+            State::Port => self.result.port = Some(component),
+            State::Pathname => self.result.pathname = Some(component),
+            State::Search => self.result.search = Some(component),
+            State::Hash => self.result.hash = Some(component),
+            State::Init | State::Authority | State::Done => {}
+        }
+
+        // If parser’s state is not "init" and new state is not "done", then:
+        if self.state != State::Init && new_state != State::Done {
+            // If parser’s state is "protocol", "authority", "username", or "password"; new state is "port", "pathname", "search", or "hash"; and parser’s result["hostname"] does not exist, then set parser’s result["hostname"] to the empty string.
+            if (State::Protocol..=State::Password).contains(&self.state)
+                && (State::Port..=State::Hash).contains(&new_state)
+                && self.result.hostname.is_none()
+            {
+                self.result.hostname = Some(String::new());
+            }
+
+            // If parser’s state is "protocol", "authority", "username", "password", "hostname", or "port"; new state is "search" or "hash"; and parser’s result["pathname"] does not exist, then:
+            if (State::Protocol..=State::Port).contains(&self.state)
+                && (State::Search..=State::Hash).contains(&new_state)
+                && self.result.pathname.is_none()
+            {
+                // If parser’s protocol matches a special scheme flag is true, then set parser’s result["pathname"] to "/".
+                // Otherwise, set parser’s result["pathname"] to the empty string.
+                self.result.pathname = Some(if self.protocol_matches_special_scheme {
+                    "/".into()
+                } else {
+                    String::new()
+                });
+            }
+
+            // If parser’s state is "protocol", "authority", "username", "password", "hostname", "port", or "pathname"; new state is "hash"; and parser’s result["search"] does not exist, then set parser’s result["search"] to the empty string.
+            if (State::Protocol..=State::Pathname).contains(&self.state)
+                && new_state == State::Hash
+                && self.result.search.is_none()
+            {
+                self.result.search = Some(String::new());
+            }
+        }
+
+        self.state = new_state;
+        self.token_index += skip;
+        self.component_start = self.token_index;
+        self.token_increment = 0;
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#rewind>
+    fn rewind(&mut self) {
+        self.token_index = self.component_start;
+        self.token_increment = 0;
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#rewind-and-set-state>
+    fn rewind_and_set_state(&mut self, state: State) {
+        self.rewind();
+        self.state = state;
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#get-a-safe-token>
+    fn safe_token(&self, index: usize) -> &Token<'a> {
+        self.tokens
+            .get(index)
+            .unwrap_or_else(|| self.tokens.last().expect("the end token"))
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#is-a-non-special-pattern-char>
+    fn is_non_special_pattern_char(&self, index: usize, value: char) -> bool {
+        matches!(
+            self.safe_token(index).kind,
+            TokenKind::Char(chr) | TokenKind::EscapedChar(chr) | TokenKind::InvalidChar(chr) if chr == value
+        )
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#is-a-protocol-suffix>
+    fn is_protocol_suffix(&self) -> bool {
+        self.is_non_special_pattern_char(self.token_index, ':')
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#next-is-authority-slashes>
+    fn next_is_authority_slashes(&self) -> bool {
+        self.is_non_special_pattern_char(self.token_index + 1, '/')
+            && self.is_non_special_pattern_char(self.token_index + 2, '/')
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#is-an-identity-terminator>
+    fn is_identity_terminator(&self) -> bool {
+        self.is_non_special_pattern_char(self.token_index, '@')
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#is-a-password-prefix>
+    fn is_password_prefix(&self) -> bool {
+        self.is_non_special_pattern_char(self.token_index, ':')
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#is-a-port-prefix>
+    fn is_port_prefix(&self) -> bool {
+        self.is_non_special_pattern_char(self.token_index, ':')
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#is-a-pathname-start>
+    fn is_pathname_start(&self) -> bool {
+        self.is_non_special_pattern_char(self.token_index, '/')
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#is-a-search-prefix>
+    fn is_search_prefix(&self) -> bool {
+        if self.is_non_special_pattern_char(self.token_index, '?') {
+            return true;
+        }
+
+        // If parser’s token list[parser’s token index]'s value is not "?", then return false.
+        if !matches!(self.tokens[self.token_index].kind, TokenKind::QuestionMark) {
+            return false;
+        }
+
+        // If previous index is less than 0, then return true.
+        let Some(previous_index) = self.token_index.checked_sub(1) else {
+            return true;
+        };
+
+        // If any of the following are true, then return false: ..
+        !matches!(
+            self.safe_token(previous_index).kind,
+            TokenKind::Name(_) | TokenKind::RegExp(_) | TokenKind::Close | TokenKind::Asterisk
+        )
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#is-a-hash-prefix>
+    fn is_hash_prefix(&self) -> bool {
+        self.is_non_special_pattern_char(self.token_index, '#')
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#is-a-group-open>
+    fn is_group_open(&self) -> bool {
+        matches!(self.tokens[self.token_index].kind, TokenKind::Open)
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#is-a-group-close>
+    fn is_group_close(&self) -> bool {
+        matches!(self.tokens[self.token_index].kind, TokenKind::Close)
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#is-an-ipv6-open>
+    fn is_ipv6_open(&self) -> bool {
+        self.is_non_special_pattern_char(self.token_index, '[')
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#is-an-ipv6-close>
+    fn is_ipv6_close(&self) -> bool {
+        self.is_non_special_pattern_char(self.token_index, ']')
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#make-a-component-string>
+    fn make_component_string(&self) -> String {
+        let end_index = self.tokens[self.token_index].span.start;
+        let start_index = self.safe_token(self.component_start).span.start;
+        self.input[start_index..end_index].to_string()
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#compute-protocol-matches-a-special-scheme-flag>
+    fn compute_protocol_matches_special_scheme(&mut self) -> Result<(), ParseError> {
+        let protocol_string = self.make_component_string();
+        let protocol_component = Component::compile(&protocol_string, &Options::default())?;
+        self.protocol_matches_special_scheme = SPECIAL_SCHEMES
+            .iter()
+            .any(|(scheme, _)| protocol_component.test(scheme));
+        Ok(())
+    }
+}
+
+/// <https://urlpattern.spec.whatwg.org/#parse-a-constructor-string>
+pub(crate) fn parse_constructor_string(input: &str) -> Result<UrlPatternInit, ParseError> {
+    ConstructorStringParser {
+        input,
+        tokens: tokenize(input, Policy::Lenient, false)?,
+        result: UrlPatternInit::default(),
+        component_start: 0,
+        token_index: 0,
+        token_increment: 1,
+        group_depth: 0,
+        hostname_ipv6_bracket_depth: 0,
+        protocol_matches_special_scheme: false,
+        state: State::Init,
+    }
+    .parse()
+}
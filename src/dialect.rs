@@ -0,0 +1,163 @@
+// SPDX-License-Identifier: MIT
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+
+/// The flavor of regular expression syntax to generate for a pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegexDialect {
+    /// ECMAScript with the `u` flag, as generated by the standard.
+    EcmaScript,
+    /// ECMAScript with the `v` (unicode sets) flag.
+    EcmaScriptUnicodeSets,
+    /// The Rust [`regex`](https://docs.rs/regex) crate.
+    Rust,
+    /// PCRE2, as used by nginx.
+    Pcre2,
+    /// RE2, as used by Envoy.
+    Re2,
+    /// POSIX extended regular expressions.
+    ///
+    /// ERE has neither lazy quantifiers nor non-capturing groups, so segment
+    /// wildcards are greedy and every group captures. Matches are the same, but
+    /// with leftmost-longest matching a segment wildcard followed by more text
+    /// in the same segment can capture more than in the other dialects. Use
+    /// [`Component::group_indices_for`](crate::Component::group_indices_for) to
+    /// find the group of each name.
+    PosixExtended,
+}
+
+impl RegexDialect {
+    /// The flags the generated ECMAScript regular expression has to be created with.
+    ///
+    /// Other dialects don't use separate flags and return an empty string.
+    pub fn flags(self, ignore_case: bool) -> &'static str {
+        match (self, ignore_case) {
+            (RegexDialect::EcmaScript, false) => "u",
+            (RegexDialect::EcmaScript, true) => "ui",
+            (RegexDialect::EcmaScriptUnicodeSets, false) => "v",
+            (RegexDialect::EcmaScriptUnicodeSets, true) => "vi",
+            _ => "",
+        }
+    }
+
+    fn is_ecmascript(self) -> bool {
+        matches!(
+            self,
+            RegexDialect::EcmaScript | RegexDialect::EcmaScriptUnicodeSets
+        )
+    }
+
+    /// Returns the prefix that makes the whole regular expression case-insensitive.
+    pub(crate) fn case_insensitive(self) -> Result<&'static str, DialectError> {
+        match self {
+            RegexDialect::EcmaScript | RegexDialect::EcmaScriptUnicodeSets => Ok(""),
+            RegexDialect::Rust | RegexDialect::Pcre2 | RegexDialect::Re2 => Ok("(?i)"),
+            RegexDialect::PosixExtended => Err(DialectError::CaseInsensitive(self)),
+        }
+    }
+
+    /// Returns the opening of a group that only groups, or of a capturing
+    /// group in dialects without non-capturing groups.
+    pub(crate) fn non_capturing_group(self) -> &'static str {
+        match self {
+            RegexDialect::PosixExtended => "(",
+            _ => "(?:",
+        }
+    }
+
+    /// Returns whether the groups of [`RegexDialect::non_capturing_group`] capture.
+    pub(crate) fn groups_capture(self) -> bool {
+        self == RegexDialect::PosixExtended
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#generate-a-segment-wildcard-regexp>
+    pub(crate) fn segment_wildcard(self, delimiter: Option<char>) -> String {
+        match (delimiter, self) {
+            (Some(delimiter), RegexDialect::PosixExtended) => {
+                format!("[^{}]+", self.escape_class_char(delimiter))
+            }
+            (Some(delimiter), _) => format!("[^{}]+?", self.escape_class_char(delimiter)),
+            // `[^]` matches everything in ECMAScript, but isn't valid elsewhere.
+            (None, _) if self.is_ecmascript() => "[^]+?".into(),
+            (None, RegexDialect::PosixExtended) => ".+".into(),
+            (None, _) => "(?s:.)+?".into(),
+        }
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#escape-a-regexp-string>
+    pub(crate) fn escape(self, input: &str) -> String {
+        let special = match self {
+            RegexDialect::EcmaScript | RegexDialect::EcmaScriptUnicodeSets => ".+*?^${}()[]|/\\",
+            // The characters `regex_syntax::is_meta_character` accepts.
+            RegexDialect::Rust => "\\.+*?()|[]{}^$#&-~",
+            RegexDialect::Pcre2 | RegexDialect::Re2 => ".+*?^${}()[]|\\",
+            RegexDialect::PosixExtended => ".[\\()*+?{|^$",
+        };
+
+        let mut result = String::with_capacity(input.len());
+        for chr in input.chars() {
+            if special.contains(chr) {
+                result.push('\\');
+            }
+            result.push(chr);
+        }
+        result
+    }
+
+    /// Escapes a single character for use inside of a character class.
+    pub(crate) fn escape_class_char(self, chr: char) -> String {
+        let special = match self {
+            RegexDialect::EcmaScript => "^$\\.*+?()[]{}|/-",
+            // The ClassSetSyntaxCharacter and ClassSetReservedPunctuator code points.
+            RegexDialect::EcmaScriptUnicodeSets => "^$\\.*+?()[]{}|/-&!#%,:;<=>@`~",
+            RegexDialect::Rust => "\\.+*?()|[]{}^$#&-~",
+            RegexDialect::Pcre2 | RegexDialect::Re2 => "^\\[]-",
+            // Backslashes are literal inside of bracket expressions, and a single
+            // character after `[^` can't be confused with anything else.
+            RegexDialect::PosixExtended => "",
+        };
+
+        if special.contains(chr) {
+            format!("\\{chr}")
+        } else {
+            chr.to_string()
+        }
+    }
+}
+
+impl fmt::Display for RegexDialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                RegexDialect::EcmaScript => "ECMAScript",
+                RegexDialect::EcmaScriptUnicodeSets => "ECMAScript (unicode sets)",
+                RegexDialect::Rust => "Rust regex",
+                RegexDialect::Pcre2 => "PCRE2",
+                RegexDialect::Re2 => "RE2",
+                RegexDialect::PosixExtended => "POSIX ERE",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DialectError {
+    CaseInsensitive(RegexDialect),
+}
+
+impl fmt::Display for DialectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DialectError::CaseInsensitive(dialect) => {
+                write!(f, "case-insensitive matching is not supported by {dialect}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for DialectError {}
@@ -0,0 +1,320 @@
+// SPDX-License-Identifier: MIT
+
+//! Generation of example inputs that match a component.
+
+use crate::automaton::{regexp_group, representative, Expr};
+use crate::{Modifier, Options, Part};
+
+/// Values for segment wildcards that look like real URL components.
+const WORDS: &[&str] = &[
+    "foo",
+    "bar",
+    "42",
+    "hello-world",
+    "2024",
+    "abc123",
+    "user",
+    "index",
+];
+
+/// Characters that are preferred when picking from a character class.
+const READABLE: &str = "abcxyz0123456789-_";
+
+/// A small deterministic pseudo-random number generator (SplitMix64), so the
+/// same component always produces the same examples.
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+/// Returns how often a part with `modifier` is repeated in the example with
+/// `index`, cycling through once, absent and many.
+fn repetitions(modifier: Option<Modifier>, index: usize) -> usize {
+    let choices: &[usize] = match modifier {
+        None => &[1],
+        Some(Modifier::Optional) => &[1, 0],
+        Some(Modifier::ZeroOrMore) => &[1, 0, 2],
+        Some(Modifier::OneOrMore) => &[1, 2, 3],
+    };
+    choices[index % choices.len()]
+}
+
+fn segment_value(options: &Options, rng: &mut Rng) -> String {
+    let words: Vec<&str> = WORDS
+        .iter()
+        .copied()
+        .filter(|word| options.delimiter.is_none_or(|chr| !word.contains(chr)))
+        .collect();
+    words[rng.below(words.len())].to_string()
+}
+
+fn full_wildcard_value(options: &Options, rng: &mut Rng) -> String {
+    let separator = options.delimiter.unwrap_or('/').to_string();
+    (0..rng.below(3))
+        .map(|_| segment_value(options, rng))
+        .collect::<Vec<_>>()
+        .join(&separator)
+}
+
+/// Picks a random input matched by `expr`, or `None` if it can't match.
+fn sample(expr: &Expr, rng: &mut Rng) -> Option<String> {
+    match expr {
+        Expr::Empty => Some(String::new()),
+        Expr::Class(class) => {
+            if class.is_empty() {
+                return None;
+            }
+            let readable: Vec<char> = READABLE
+                .chars()
+                .filter(|&chr| {
+                    class
+                        .iter()
+                        .any(|&(start, end)| (start..=end).contains(&(chr as u32)))
+                })
+                .collect();
+            if !readable.is_empty() {
+                return Some(readable[rng.below(readable.len())].to_string());
+            }
+            let (start, end) = class[rng.below(class.len())];
+            Some(representative(start, end).to_string())
+        }
+        Expr::Concat(exprs) => exprs.iter().map(|expr| sample(expr, rng)).collect(),
+        Expr::Alternation(exprs) => {
+            let first = rng.below(exprs.len());
+            (0..exprs.len()).find_map(|offset| sample(&exprs[(first + offset) % exprs.len()], rng))
+        }
+        Expr::Repeat(expr) => (0..rng.below(3)).map(|_| sample(expr, rng)).collect(),
+    }
+}
+
+/// Generates one candidate input, which doesn't necessarily match.
+pub(crate) fn generate(parts: &[Part], options: &Options, index: usize, rng: &mut Rng) -> String {
+    let mut input = String::new();
+
+    for (position, part) in parts.iter().enumerate() {
+        let (modifier, prefix, suffix) = match part {
+            Part::FixedText { value, modifier } => {
+                input.push_str(&value.repeat(repetitions(*modifier, index + position)));
+                continue;
+            }
+            Part::SegmentWildcard {
+                modifier,
+                prefix,
+                suffix,
+                ..
+            }
+            | Part::FullWildcard {
+                modifier,
+                prefix,
+                suffix,
+                ..
+            }
+            | Part::RegExp {
+                modifier,
+                prefix,
+                suffix,
+                ..
+            } => (modifier, prefix, suffix),
+        };
+
+        // Repeating `prefix value suffix` matches `prefix value (suffix prefix value)* suffix`.
+        for _ in 0..repetitions(*modifier, index + position) {
+            let value = match part {
+                Part::SegmentWildcard { .. } => segment_value(options, rng),
+                Part::FullWildcard { .. } => full_wildcard_value(options, rng),
+                Part::RegExp { value, .. } => regexp_group(value, options)
+                    .and_then(|expr| sample(&expr, rng))
+                    .unwrap_or_default(),
+                Part::FixedText { .. } => unreachable!(),
+            };
+            input.push_str(prefix);
+            input.push_str(&value);
+            input.push_str(suffix);
+        }
+    }
+
+    input
+}
+
+/// Builds a [`proptest`] strategy for inputs that match `parts`, from a
+/// strategy for the values of every part.
+///
+/// Values of groups with a validation function in `validators`, by capture
+/// group index, are filtered with that function.
+#[cfg(feature = "proptest")]
+pub(crate) fn strategy(
+    parts: &[Part],
+    options: &Options,
+    validators: &[(usize, crate::Validate)],
+) -> Result<proptest::strategy::BoxedStrategy<String>, crate::AnalysisError> {
+    use proptest::collection::vec;
+    use proptest::strategy::{Just, Strategy};
+    use proptest::string::string_regex;
+
+    use crate::{full_wildcard_regexp, RegexDialect};
+
+    let mut strategy = Just(String::new()).boxed();
+    let mut group = 0;
+    for part in parts {
+        let (name, modifier, value, prefix, suffix) = match part {
+            Part::FixedText { value, modifier } => {
+                let value = value.clone();
+                let count = repetition_range(*modifier);
+                strategy = (strategy, count)
+                    .prop_map(move |(input, count)| input + &value.repeat(count))
+                    .boxed();
+                continue;
+            }
+            Part::SegmentWildcard {
+                name,
+                modifier,
+                prefix,
+                suffix,
+            } => {
+                let value = match options.delimiter {
+                    Some(chr) => format!("[^{}]+", RegexDialect::Rust.escape_class_char(chr)),
+                    None => "(?s:.)+".into(),
+                };
+                (name, modifier, value, prefix, suffix)
+            }
+            Part::FullWildcard {
+                name,
+                modifier,
+                prefix,
+                suffix,
+            } => (
+                name,
+                modifier,
+                full_wildcard_regexp().into(),
+                prefix,
+                suffix,
+            ),
+            Part::RegExp {
+                name,
+                value,
+                modifier,
+                prefix,
+                suffix,
+                ..
+            } => (name, modifier, value.clone(), prefix, suffix),
+        };
+        group += 1;
+
+        let mut value = string_regex(&value)
+            .map_err(|_| crate::AnalysisError::UnsupportedRegExp(name.clone()))?
+            .boxed();
+        if let Some(&(_, validate)) = validators.iter().find(|&&(index, _)| index == group) {
+            value = value
+                .prop_filter("value isn't valid", move |value| validate(value))
+                .boxed();
+        }
+
+        // Repeating `prefix value suffix` matches `prefix value (suffix prefix value)* suffix`.
+        let (prefix, suffix) = (prefix.clone(), suffix.clone());
+        let values = vec(value, repetition_range(*modifier));
+        strategy = (strategy, values)
+            .prop_map(move |(mut input, values)| {
+                for value in values {
+                    input.push_str(&prefix);
+                    input.push_str(&value);
+                    input.push_str(&suffix);
+                }
+                input
+            })
+            .boxed();
+    }
+    Ok(strategy)
+}
+
+/// The range of how often a part with `modifier` is repeated in a strategy.
+#[cfg(feature = "proptest")]
+fn repetition_range(modifier: Option<Modifier>) -> core::ops::RangeInclusive<usize> {
+    match modifier {
+        None => 1..=1,
+        Some(Modifier::Optional) => 0..=1,
+        Some(Modifier::ZeroOrMore) => 0..=3,
+        Some(Modifier::OneOrMore) => 1..=3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Component, Options};
+
+    #[test]
+    fn examples() {
+        let component =
+            Component::compile("/users/:id/files/:path*", &Options::pathname()).unwrap();
+        let examples = component.examples(5);
+        assert_eq!(examples.len(), 5);
+        assert!(examples.iter().all(|example| component.test(example)));
+        assert!(examples
+            .iter()
+            .any(|example| example.matches('/').count() == 3));
+        assert!(examples
+            .iter()
+            .any(|example| example.matches('/').count() == 5));
+
+        let component =
+            Component::compile(r"/:id(\d{3})-:name([a-z]+)", &Options::pathname()).unwrap();
+        assert!(component
+            .examples(3)
+            .iter()
+            .all(|example| component.test(example)));
+
+        let hostname = Component::compile(":sub.example.com", &Options::hostname()).unwrap();
+        assert!(hostname
+            .examples(4)
+            .iter()
+            .all(|example| example.matches('.').count() == 2));
+
+        let empty = Component::compile("/([^\\s\\S])", &Options::pathname()).unwrap();
+        assert!(empty.examples(3).is_empty());
+    }
+
+    #[cfg(feature = "proptest")]
+    #[test]
+    fn input_strategy() {
+        use proptest::strategy::{Strategy, ValueTree};
+        use proptest::test_runner::TestRunner;
+
+        use crate::Constraints;
+
+        let options = Options {
+            constraints: Constraints::builtin(),
+            ..Options::pathname()
+        };
+        let mut runner = TestRunner::deterministic();
+        for pattern in [
+            "/users/:id/files/:path*",
+            r"/:id(\d{3})-:name([a-z]+)?",
+            "/{a}+/*.:ext",
+            "/posts/:id<int>/:slug<slug>+",
+        ] {
+            let component = Component::compile(pattern, &options).unwrap();
+            let strategy = component.input_strategy().unwrap();
+            for _ in 0..64 {
+                let input = strategy.new_tree(&mut runner).unwrap().current();
+                assert!(component.test(&input), "{pattern} doesn't match {input:?}");
+            }
+        }
+
+        let component = Component::compile(r"/:word(\bx)", &options).unwrap();
+        assert!(component.input_strategy().is_err());
+    }
+}
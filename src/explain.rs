@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: MIT
+
+//! Explanations for why an input doesn't match a component.
+
+use std::fmt;
+
+use regex::Regex;
+
+use crate::{generate_pattern_string, generate_regexp, Options, Part, RegexDialect, Validate};
+
+/// Why an input was rejected by a component.
+///
+/// The parts are matched against the input in order, until a part can't
+/// match what is left of the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    /// The longest prefix of the input that the parts before [`Explanation::part`] match.
+    pub matched: String,
+    /// The index of the part that rejected the input.
+    pub index: usize,
+    /// The part that rejected the input, or `None` if every part matched but
+    /// the input didn't end.
+    pub part: Option<Part>,
+    /// The pattern string of the part that rejected the input.
+    pub expected: String,
+    /// The rest of the input after the matched prefix.
+    pub actual: String,
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.part {
+            Some(_) => write!(
+                f,
+                "matched {:?}, then expected `{}` but found {:?}",
+                self.matched, self.expected, self.actual
+            ),
+            None => write!(
+                f,
+                "matched {:?}, then expected the end of the input but found {:?}",
+                self.matched, self.actual
+            ),
+        }
+    }
+}
+
+/// Compiles the regular expressions for every prefix of `parts`, from the
+/// empty prefix to all of `parts`.
+pub(crate) fn prefix_regexps(parts: &[Part], options: &Options) -> Vec<Regex> {
+    (0..=parts.len())
+        .map(|index| {
+            let (regexp, ..) = generate_regexp(&parts[..index], options, RegexDialect::Rust)
+                .expect("Rust regex supports every pattern");
+            Regex::new(&regexp).expect("parts were compiled before")
+        })
+        .collect()
+}
+
+/// Explains why `input` doesn't match `parts`, or returns `None` if it does.
+///
+/// `validators` are the validation functions of constraints, by capture
+/// group index, and `regexps` are the [`prefix_regexps`] of `parts`.
+pub(crate) fn explain(
+    parts: &[Part],
+    options: &Options,
+    validators: &[(usize, Validate)],
+    regexps: &[Regex],
+    input: &str,
+) -> Option<Explanation> {
+    let mut ends: Vec<usize> = input.char_indices().map(|(index, _)| index).collect();
+    ends.push(input.len());
+
+    for (index, regexp) in regexps.iter().enumerate().rev() {
+        let groups = parts[..index]
+            .iter()
+            .filter(|part| !matches!(part, Part::FixedText { .. }))
+            .count();
+        let matches = |prefix: &str| {
+            let Some(captures) = regexp.captures(prefix) else {
+                return false;
+            };
+            validators
+                .iter()
+                .filter(|&&(group, _)| group <= groups)
+                .all(|&(group, validate)| captures.get(group).is_none_or(|m| validate(m.as_str())))
+        };
+
+        let Some(&end) = ends.iter().rev().find(|&&end| matches(&input[..end])) else {
+            continue;
+        };
+        if index == parts.len() && end == input.len() {
+            return None;
+        }
+
+        let part = parts.get(index).cloned();
+        let expected = part
+            .as_ref()
+            .map(|part| generate_pattern_string(std::slice::from_ref(part), options))
+            .unwrap_or_default();
+        return Some(Explanation {
+            matched: input[..end].to_string(),
+            index,
+            part,
+            expected,
+            actual: input[end..].to_string(),
+        });
+    }
+
+    // The empty part list matches the empty prefix of every input.
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Component, Options, Part};
+
+    #[test]
+    fn explain() {
+        let component = Component::compile("/users/:id/edit", &Options::pathname()).unwrap();
+        assert_eq!(component.explain("/users/5/edit"), None);
+
+        let explanation = component.explain("/users/5/view").unwrap();
+        assert_eq!(explanation.matched, "/users/5");
+        assert_eq!(explanation.index, 2);
+        assert_eq!(
+            explanation.part,
+            Some(Part::FixedText {
+                value: "/edit".into(),
+                modifier: None
+            })
+        );
+        assert_eq!(explanation.expected, "/edit");
+        assert_eq!(explanation.actual, "/view");
+
+        let explanation = component.explain("/users/5/edit/now").unwrap();
+        assert_eq!(explanation.part, None);
+        assert_eq!(
+            explanation.to_string(),
+            r#"matched "/users/5/edit", then expected the end of the input but found "/now""#
+        );
+
+        let component = Component::compile(r"/posts/:id(\d+)", &Options::pathname()).unwrap();
+        let explanation = component.explain("/posts/latest").unwrap();
+        assert_eq!(explanation.matched, "/posts");
+        assert_eq!(explanation.expected, r"/:id(\d+)");
+        assert_eq!(explanation.actual, "/latest");
+    }
+}
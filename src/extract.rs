@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: MIT
+
+//! Conversion of match results into typed values.
+//!
+//! The `FromUrlPattern` derive macro from the `url-pattern-macros` crate
+//! implements [`FromUrlPattern`] for structs with named fields.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{Component, ComponentResult, Modifier, Part};
+
+/// The kind of group a field is filled from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupKind {
+    /// A group without a modifier, for a field of any type implementing `FromStr`.
+    Required,
+    /// A group with the `?` modifier, for an `Option` field.
+    Optional,
+    /// A group with the `*` or `+` modifier, for a `Vec` field.
+    Repeated,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ExtractError {
+    #[error("the pattern has no group `{0}`")]
+    UnknownGroup(String),
+    #[error("group `{name}` needs {expected}")]
+    Modifier {
+        name: String,
+        expected: &'static str,
+    },
+    #[error("group `{0}` didn't match")]
+    Missing(String),
+    #[error("can't parse {value:?} for `{name}`: {message}")]
+    Parse {
+        name: String,
+        value: String,
+        message: String,
+    },
+}
+
+/// The groups of a match result, as seen by [`FromUrlPattern::from_groups`].
+pub struct Groups<'a> {
+    result: &'a ComponentResult,
+    parts: &'a [Part],
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, ExtractError>
+where
+    T::Err: Display,
+{
+    value.parse().map_err(|err: T::Err| ExtractError::Parse {
+        name: name.into(),
+        value: value.into(),
+        message: err.to_string(),
+    })
+}
+
+impl<'a> Groups<'a> {
+    fn value(&self, name: &str) -> Option<&'a str> {
+        self.result.groups.get(name)?.as_deref()
+    }
+
+    /// The text between two repetitions of the group `name`: its suffix
+    /// followed by its prefix.
+    fn separator(&self, name: &str) -> String {
+        self.parts
+            .iter()
+            .find_map(|part| match part {
+                Part::FixedText { .. } => None,
+                Part::SegmentWildcard {
+                    name: part_name,
+                    prefix,
+                    suffix,
+                    ..
+                }
+                | Part::FullWildcard {
+                    name: part_name,
+                    prefix,
+                    suffix,
+                    ..
+                }
+                | Part::RegExp {
+                    name: part_name,
+                    prefix,
+                    suffix,
+                    ..
+                } => (part_name == name).then(|| format!("{suffix}{prefix}")),
+            })
+            .unwrap_or_default()
+    }
+
+    /// Parses the value of a group without a modifier.
+    pub fn required<T: FromStr>(&self, name: &str) -> Result<T, ExtractError>
+    where
+        T::Err: Display,
+    {
+        let value = self
+            .value(name)
+            .ok_or_else(|| ExtractError::Missing(name.into()))?;
+        parse(name, value)
+    }
+
+    /// Parses the value of an optional group, if it matched.
+    pub fn optional<T: FromStr>(&self, name: &str) -> Result<Option<T>, ExtractError>
+    where
+        T::Err: Display,
+    {
+        self.value(name).map(|value| parse(name, value)).transpose()
+    }
+
+    /// Splits the value of a repeated group into its repetitions and parses
+    /// every one of them.
+    ///
+    /// The repetitions are separated by the suffix and prefix of the group,
+    /// like `/` for `/:path+` or `-` for `{-:x}+`. A group without a prefix
+    /// or suffix can't be split and is parsed as a whole.
+    pub fn repeated<T: FromStr>(&self, name: &str) -> Result<Vec<T>, ExtractError>
+    where
+        T::Err: Display,
+    {
+        let Some(value) = self.value(name) else {
+            return Ok(vec![]);
+        };
+        let separator = self.separator(name);
+        if separator.is_empty() {
+            return Ok(vec![parse(name, value)?]);
+        }
+        value
+            .split(separator.as_str())
+            .map(|segment| parse(name, segment))
+            .collect()
+    }
+}
+
+/// A type that can be filled from the groups of a match result.
+///
+/// Usually implemented with `#[derive(FromUrlPattern)]`.
+pub trait FromUrlPattern: Sized {
+    /// The names of the groups the type reads and their kind.
+    const GROUPS: &'static [(&'static str, GroupKind)];
+
+    fn from_groups(groups: &Groups<'_>) -> Result<Self, ExtractError>;
+
+    /// Checks that `component` has every group in [`FromUrlPattern::GROUPS`]
+    /// with a fitting modifier.
+    fn check(component: &Component) -> Result<(), ExtractError> {
+        for &(name, kind) in Self::GROUPS {
+            let modifier = component
+                .parts()
+                .iter()
+                .find_map(|part| match part {
+                    Part::SegmentWildcard {
+                        name: part_name,
+                        modifier,
+                        ..
+                    }
+                    | Part::FullWildcard {
+                        name: part_name,
+                        modifier,
+                        ..
+                    }
+                    | Part::RegExp {
+                        name: part_name,
+                        modifier,
+                        ..
+                    } if part_name == name => Some(*modifier),
+                    _ => None,
+                })
+                .ok_or_else(|| ExtractError::UnknownGroup(name.into()))?;
+
+            let expected = match (kind, modifier) {
+                (GroupKind::Required, None)
+                | (GroupKind::Optional, Some(Modifier::Optional))
+                | (GroupKind::Repeated, Some(Modifier::ZeroOrMore | Modifier::OneOrMore)) => {
+                    continue
+                }
+                (GroupKind::Required, _) => "no modifier",
+                (GroupKind::Optional, _) => "the `?` modifier",
+                (GroupKind::Repeated, _) => "the `*` or `+` modifier",
+            };
+            return Err(ExtractError::Modifier {
+                name: name.into(),
+                expected,
+            });
+        }
+        Ok(())
+    }
+
+    /// Fills the type from a match result of `component`.
+    fn from_result(result: &ComponentResult, component: &Component) -> Result<Self, ExtractError> {
+        Self::check(component)?;
+        Self::from_groups(&Groups {
+            result,
+            parts: component.parts(),
+        })
+    }
+}
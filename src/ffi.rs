@@ -0,0 +1,367 @@
+// SPDX-License-Identifier: MIT
+
+//! A C interface for compiling and matching [`UrlPattern`]s.
+//!
+//! The declarations are in `include/url_pattern.h`, which is generated from
+//! this file by `tests/ffi.rs`. Set `URL_PATTERN_UPDATE_HEADER=1` when running
+//! that test to rewrite the header after changing a signature.
+//!
+//! The crate only builds an `rlib`. A shared or static library for linking
+//! from C is built with
+//! `cargo rustc --release --lib --features ffi --crate-type cdylib` (or
+//! `staticlib`).
+//!
+//! All strings are NUL-terminated UTF-8. Every object returned by a function
+//! has to be released with the matching `_free` function, and strings returned
+//! by a function live as long as the object they were returned for.
+//!
+//! Panics never unwind into C. A function that panics returns NULL or false,
+//! and stores an error if it has an error out-parameter.
+
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::{UrlPattern, UrlPatternError, UrlPatternOptions};
+
+/// Why [`url_pattern_new`], [`url_pattern_test`] or [`url_pattern_exec`]
+/// failed.
+pub struct UrlPatternFfiError {
+    message: CString,
+    component: Option<CString>,
+    pattern: Option<CString>,
+    position: Option<usize>,
+}
+
+impl UrlPatternFfiError {
+    fn new(error: &dyn std::fmt::Display) -> Box<UrlPatternFfiError> {
+        Box::new(UrlPatternFfiError {
+            message: c_string(&error.to_string()),
+            component: None,
+            pattern: None,
+            position: None,
+        })
+    }
+}
+
+/// The groups of a match, iterated with [`url_pattern_groups_next`].
+pub struct UrlPatternGroups {
+    /// The component, name and value of every group.
+    groups: Vec<(&'static CStr, CString, Option<CString>)>,
+    next: usize,
+}
+
+fn c_string(value: &str) -> CString {
+    // Strings from C and anything derived from them can't contain NUL.
+    CString::new(value.replace('\0', "")).expect("no NUL bytes")
+}
+
+/// # Safety
+///
+/// `value` has to be null or a valid NUL-terminated string.
+unsafe fn str_arg<'a>(value: *const c_char) -> Option<Result<&'a str, std::str::Utf8Error>> {
+    (!value.is_null()).then(|| CStr::from_ptr(value).to_str())
+}
+
+/// Like [`str_arg`], but fails for NULL.
+///
+/// # Safety
+///
+/// `value` has to be null or a valid NUL-terminated string.
+unsafe fn required_str_arg<'a>(
+    value: *const c_char,
+    name: &str,
+) -> Result<&'a str, Box<UrlPatternFfiError>> {
+    match str_arg(value) {
+        Some(Ok(value)) => Ok(value),
+        Some(Err(utf8)) => Err(UrlPatternFfiError::new(&utf8)),
+        None => Err(UrlPatternFfiError::new(&format_args!("the {name} is NULL"))),
+    }
+}
+
+/// Runs `body`, or returns `default` if it panics.
+fn guard<T>(default: T, body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or(default)
+}
+
+/// Runs `body` and stores its error, or an error for a panic, in `error`
+/// unless it is NULL.
+///
+/// # Safety
+///
+/// `error` has to be NULL or valid for writes.
+unsafe fn fallible<T>(
+    error: *mut *mut UrlPatternFfiError,
+    body: impl FnOnce() -> Result<T, Box<UrlPatternFfiError>>,
+) -> Option<T> {
+    let result = panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown cause");
+        Err(UrlPatternFfiError::new(&format_args!(
+            "internal error: {message}"
+        )))
+    });
+    match result {
+        Ok(value) => Some(value),
+        Err(failure) => {
+            if !error.is_null() {
+                *error = Box::into_raw(failure);
+            }
+            None
+        }
+    }
+}
+
+/// Compiles a constructor string like `https://:tenant.example.com/users/:id`,
+/// relative to `base_url` unless it is NULL.
+///
+/// Returns NULL on failure, and if `error` isn't NULL stores an error in it
+/// that has to be released with `url_pattern_error_free`.
+///
+/// # Safety
+///
+/// `input` has to be a valid string, `base_url` NULL or a valid string and
+/// `error` NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn url_pattern_new(
+    input: *const c_char,
+    base_url: *const c_char,
+    ignore_case: bool,
+    error: *mut *mut UrlPatternFfiError,
+) -> *mut UrlPattern {
+    let pattern = fallible(error, || {
+        let input = required_str_arg(input, "input")?;
+        let base_url = str_arg(base_url)
+            .transpose()
+            .map_err(|utf8| UrlPatternFfiError::new(&utf8))?;
+
+        UrlPattern::parse(input, base_url, &UrlPatternOptions { ignore_case }).map_err(
+            |parse_error| {
+                let mut failure = UrlPatternFfiError::new(&parse_error);
+                if let UrlPatternError::Component {
+                    component,
+                    pattern,
+                    position,
+                    ..
+                } = parse_error
+                {
+                    failure.component = Some(c_string(component));
+                    failure.pattern = Some(c_string(&pattern));
+                    failure.position = position;
+                }
+                failure
+            },
+        )
+    });
+    pattern.map_or(ptr::null_mut(), |pattern| Box::into_raw(Box::new(pattern)))
+}
+
+/// Returns whether the URL `url` matches.
+///
+/// Returns false if an argument is NULL or `url` isn't UTF-8, and if `error`
+/// isn't NULL stores an error in it that has to be released with
+/// `url_pattern_error_free`.
+///
+/// # Safety
+///
+/// `pattern` has to be NULL or returned by `url_pattern_new`, `url` NULL or a
+/// valid string and `error` NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn url_pattern_test(
+    pattern: *const UrlPattern,
+    url: *const c_char,
+    error: *mut *mut UrlPatternFfiError,
+) -> bool {
+    fallible(error, || {
+        let pattern = pattern
+            .as_ref()
+            .ok_or_else(|| UrlPatternFfiError::new(&"the pattern is NULL"))?;
+        Ok(pattern.test_url(required_str_arg(url, "URL")?))
+    })
+    .unwrap_or(false)
+}
+
+/// Matches the URL `url` and returns an iterator over the groups of every
+/// component, or NULL if it doesn't match. The iterator has to be released
+/// with `url_pattern_groups_free`.
+///
+/// Like `url_pattern_test`, stores an error in `error` for invalid arguments.
+///
+/// # Safety
+///
+/// `pattern` has to be NULL or returned by `url_pattern_new`, `url` NULL or a
+/// valid string and `error` NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn url_pattern_exec(
+    pattern: *const UrlPattern,
+    url: *const c_char,
+    error: *mut *mut UrlPatternFfiError,
+) -> *mut UrlPatternGroups {
+    let groups = fallible(error, || {
+        let pattern = pattern
+            .as_ref()
+            .ok_or_else(|| UrlPatternFfiError::new(&"the pattern is NULL"))?;
+        Ok(groups(pattern, required_str_arg(url, "URL")?))
+    });
+    groups
+        .flatten()
+        .map_or(ptr::null_mut(), |groups| Box::into_raw(Box::new(groups)))
+}
+
+fn groups(pattern: &UrlPattern, url: &str) -> Option<UrlPatternGroups> {
+    let result = pattern.exec_url(url)?;
+
+    let results = [
+        (c"protocol", result.protocol),
+        (c"username", result.username),
+        (c"password", result.password),
+        (c"hostname", result.hostname),
+        (c"port", result.port),
+        (c"pathname", result.pathname),
+        (c"search", result.search),
+        (c"hash", result.hash),
+    ];
+    let mut groups = vec![];
+    for ((component, mut result), compiled) in results.into_iter().zip(pattern.components()) {
+        // In the order of the groups in the pattern.
+        for name in compiled.group_names() {
+            let value = result.groups.remove(name).flatten();
+            groups.push((component, c_string(name), value.as_deref().map(c_string)));
+        }
+    }
+    Some(UrlPatternGroups { groups, next: 0 })
+}
+
+/// Advances to the next group and stores its component, name and value, or
+/// returns false if there are no more groups. The value is NULL for an
+/// optional group that didn't match.
+///
+/// # Safety
+///
+/// `groups` has to be returned by `url_pattern_exec`, and the other arguments
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn url_pattern_groups_next(
+    groups: *mut UrlPatternGroups,
+    component: *mut *const c_char,
+    name: *mut *const c_char,
+    value: *mut *const c_char,
+) -> bool {
+    guard(false, || {
+        let Some(groups) = groups.as_mut() else {
+            return false;
+        };
+        let Some((group_component, group_name, group_value)) = groups.groups.get(groups.next)
+        else {
+            return false;
+        };
+        groups.next += 1;
+
+        *component = group_component.as_ptr();
+        *name = group_name.as_ptr();
+        *value = group_value
+            .as_ref()
+            .map_or(ptr::null(), |value| value.as_ptr());
+        true
+    })
+}
+
+/// # Safety
+///
+/// `groups` has to be NULL or returned by `url_pattern_exec`, and not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn url_pattern_groups_free(groups: *mut UrlPatternGroups) {
+    if !groups.is_null() {
+        guard((), || drop(Box::from_raw(groups)));
+    }
+}
+
+/// # Safety
+///
+/// `pattern` has to be NULL or returned by `url_pattern_new`, and not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn url_pattern_free(pattern: *mut UrlPattern) {
+    if !pattern.is_null() {
+        guard((), || drop(Box::from_raw(pattern)));
+    }
+}
+
+/// A description of the error.
+///
+/// # Safety
+///
+/// `error` has to be an error stored by another function.
+#[no_mangle]
+pub unsafe extern "C" fn url_pattern_error_message(
+    error: *const UrlPatternFfiError,
+) -> *const c_char {
+    guard(ptr::null(), || (*error).message.as_ptr())
+}
+
+/// The name of the component whose pattern is invalid, like `pathname`, or
+/// NULL if the error isn't about a single component.
+///
+/// # Safety
+///
+/// `error` has to be an error stored by another function.
+#[no_mangle]
+pub unsafe extern "C" fn url_pattern_error_component(
+    error: *const UrlPatternFfiError,
+) -> *const c_char {
+    guard(ptr::null(), || {
+        (*error)
+            .component
+            .as_ref()
+            .map_or(ptr::null(), |component| component.as_ptr())
+    })
+}
+
+/// The pattern of the component whose pattern is invalid, or NULL if the error
+/// isn't about a single component.
+///
+/// # Safety
+///
+/// `error` has to be an error stored by another function.
+#[no_mangle]
+pub unsafe extern "C" fn url_pattern_error_pattern(
+    error: *const UrlPatternFfiError,
+) -> *const c_char {
+    guard(ptr::null(), || {
+        (*error)
+            .pattern
+            .as_ref()
+            .map_or(ptr::null(), |pattern| pattern.as_ptr())
+    })
+}
+
+/// The byte offset in the pattern returned by `url_pattern_error_pattern` at
+/// which parsing failed, or -1 if it is unknown.
+///
+/// # Safety
+///
+/// `error` has to be an error stored by another function.
+#[no_mangle]
+pub unsafe extern "C" fn url_pattern_error_position(error: *const UrlPatternFfiError) -> isize {
+    guard(-1, || {
+        (*error)
+            .position
+            .and_then(|position| isize::try_from(position).ok())
+            .unwrap_or(-1)
+    })
+}
+
+/// # Safety
+///
+/// `error` has to be NULL or an error stored by another function, and not be
+/// used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn url_pattern_error_free(error: *mut UrlPatternFfiError) {
+    if !error.is_null() {
+        guard((), || drop(Box::from_raw(error)));
+    }
+}
@@ -0,0 +1,291 @@
+// SPDX-License-Identifier: MIT
+
+//! A front end for glob patterns like `/static/**/*.js`.
+//!
+//! A glob is parsed into the same parts as a pattern string, so it can be
+//! compiled with [`Component::from_parts`](crate::Component::from_parts):
+//!
+//! - `*` matches any text within a segment, as an optional segment wildcard.
+//! - `**` matches any text. A `**` that makes up a whole segment also matches
+//!   no segment at all, so `/static/**/*.js` matches `/static/app.js`.
+//! - `?` matches a single character other than the delimiter.
+//! - `[abc]`, `[a-z]` and the negated `[!abc]` or `[^abc]` match a single
+//!   character, and `{a,b}` matches one of the alternatives. Both become
+//!   regexp parts.
+//! - `\` escapes the next character.
+
+use thiserror::Error;
+
+use crate::{Modifier, Options, Part, RegexDialect};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum GlobError {
+    #[error("trailing backslash `\\` without a character to escape")]
+    TrailingBackslash,
+    #[error("missing closing `]` for the character class at index {0}")]
+    UnclosedClass(usize),
+    #[error("missing closing `}}` for the alternation at index {0}")]
+    UnclosedAlternation(usize),
+}
+
+struct GlobParser<'a> {
+    chars: Vec<(usize, char)>,
+    position: usize,
+    options: &'a Options,
+    pending_fixed_value: String,
+    next_numeric_name: usize,
+    parts: Vec<Part>,
+}
+
+impl GlobParser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).map(|&(_, chr)| chr)
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let chr = self.peek()?;
+        self.position += 1;
+        Some(chr)
+    }
+
+    fn is_delimiter(&self, chr: Option<char>) -> bool {
+        chr.is_some() && chr == self.options.delimiter
+    }
+
+    fn escaped(&mut self) -> Result<char, GlobError> {
+        self.next().ok_or(GlobError::TrailingBackslash)
+    }
+
+    fn flush(&mut self) {
+        if !self.pending_fixed_value.is_empty() {
+            self.parts.push(Part::FixedText {
+                value: std::mem::take(&mut self.pending_fixed_value),
+                modifier: None,
+            });
+        }
+    }
+
+    fn name(&mut self) -> String {
+        let name = self.next_numeric_name.to_string();
+        self.next_numeric_name += 1;
+        name
+    }
+
+    /// A regexp matching a single character other than the delimiter.
+    fn segment_char(&self) -> String {
+        match self.options.delimiter {
+            Some(delimiter) => format!(
+                "[^{}]",
+                RegexDialect::EcmaScript.escape_class_char(delimiter)
+            ),
+            None => ".".into(),
+        }
+    }
+
+    fn regexp(&mut self, value: String) {
+        self.flush();
+        let name = self.name();
+        self.parts.push(Part::RegExp {
+            name,
+            value,
+            modifier: None,
+            prefix: String::new(),
+            suffix: String::new(),
+            constraint: None,
+        });
+    }
+
+    fn parse(&mut self) -> Result<(), GlobError> {
+        while let Some(chr) = self.next() {
+            match chr {
+                '\\' => {
+                    let chr = self.escaped()?;
+                    self.pending_fixed_value.push(chr);
+                }
+                '*' if self.peek() == Some('*') => {
+                    while self.peek() == Some('*') {
+                        self.position += 1;
+                    }
+                    self.globstar();
+                }
+                '*' => {
+                    self.flush();
+                    let name = self.name();
+                    self.parts.push(Part::SegmentWildcard {
+                        name,
+                        modifier: Some(Modifier::Optional),
+                        prefix: String::new(),
+                        suffix: String::new(),
+                    });
+                }
+                '?' => self.regexp(self.segment_char()),
+                '[' => {
+                    let class = self.class()?;
+                    self.regexp(class);
+                }
+                '{' => {
+                    let alternation = self.alternation()?;
+                    self.regexp(alternation);
+                }
+                _ => self.pending_fixed_value.push(chr),
+            }
+        }
+        self.flush();
+        Ok(())
+    }
+
+    /// Adds a full wildcard for `**`. If it makes up a whole segment, the
+    /// delimiter next to it becomes its prefix or suffix and it is optional.
+    fn globstar(&mut self) {
+        let delimiter: String = self.options.delimiter.into_iter().collect();
+        let ends_segment = self.peek().is_none() || self.is_delimiter(self.peek());
+
+        let (prefix, suffix, modifier) = if ends_segment
+            && !delimiter.is_empty()
+            && self.pending_fixed_value.ends_with(&delimiter)
+        {
+            self.pending_fixed_value.pop();
+            (delimiter, String::new(), Some(Modifier::Optional))
+        } else if self.parts.is_empty()
+            && self.pending_fixed_value.is_empty()
+            && self.is_delimiter(self.peek())
+        {
+            self.position += 1;
+            (String::new(), delimiter, Some(Modifier::Optional))
+        } else {
+            (String::new(), String::new(), None)
+        };
+
+        self.flush();
+        let name = self.name();
+        self.parts.push(Part::FullWildcard {
+            name,
+            modifier,
+            prefix,
+            suffix,
+        });
+    }
+
+    /// Parses a character class after the `[` into a regexp.
+    fn class(&mut self) -> Result<String, GlobError> {
+        let start = self.chars[self.position - 1].0;
+        let dialect = RegexDialect::EcmaScript;
+
+        let negated = matches!(self.peek(), Some('!' | '^'));
+        if negated {
+            self.position += 1;
+        }
+
+        let mut members = vec![];
+        loop {
+            match self.next() {
+                None => return Err(GlobError::UnclosedClass(start)),
+                // A `]` right after the `[` is a member of the class.
+                Some(']') if !members.is_empty() => break,
+                Some('\\') => members.push(Some(self.escaped()?)),
+                // `None` stands for the `-` of a range.
+                Some('-') if !members.is_empty() && self.peek() != Some(']') => members.push(None),
+                Some(chr) => members.push(Some(chr)),
+            }
+        }
+
+        let mut class = String::from(if negated { "[^" } else { "[" });
+        for member in members {
+            match member {
+                Some(chr) => class.push_str(&dialect.escape_class_char(chr)),
+                None => class.push('-'),
+            }
+        }
+        // A negated class still doesn't match the delimiter.
+        if let Some(delimiter) = self.options.delimiter.filter(|_| negated) {
+            class.push_str(&dialect.escape_class_char(delimiter));
+        }
+        class.push(']');
+        Ok(class)
+    }
+
+    /// Parses an alternation after the `{` into a regexp.
+    fn alternation(&mut self) -> Result<String, GlobError> {
+        let start = self.chars[self.position - 1].0;
+        let dialect = RegexDialect::EcmaScript;
+
+        let mut regexp = String::new();
+        loop {
+            match self.next() {
+                None => return Err(GlobError::UnclosedAlternation(start)),
+                Some('}') => return Ok(regexp),
+                Some(',') => regexp.push('|'),
+                Some('\\') => {
+                    let chr = self.escaped()?;
+                    regexp.push_str(&dialect.escape(&chr.to_string()));
+                }
+                Some('*') if self.peek() == Some('*') => {
+                    while self.peek() == Some('*') {
+                        self.position += 1;
+                    }
+                    regexp.push_str(".*");
+                }
+                Some('*') => regexp.push_str(&format!("{}*", self.segment_char())),
+                Some('?') => regexp.push_str(&self.segment_char()),
+                Some('[') => regexp.push_str(&self.class()?),
+                Some('{') => regexp.push_str(&format!("(?:{})", self.alternation()?)),
+                Some(chr) => regexp.push_str(&dialect.escape(&chr.to_string())),
+            }
+        }
+    }
+}
+
+/// Parses a glob into the parts of a pattern, using the delimiter of
+/// `options` as the segment separator.
+pub fn parse_glob(input: &str, options: &Options) -> Result<Vec<Part>, GlobError> {
+    let mut parser = GlobParser {
+        chars: input.char_indices().collect(),
+        position: 0,
+        options,
+        pending_fixed_value: String::new(),
+        next_numeric_name: 0,
+        parts: vec![],
+    };
+    parser.parse()?;
+    Ok(parser.parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Component;
+
+    fn glob(input: &str) -> Component {
+        let options = Options::pathname();
+        Component::from_parts(parse_glob(input, &options).unwrap(), &options).unwrap()
+    }
+
+    #[test]
+    fn globs() {
+        let scripts = glob("/static/**/*.js");
+        assert!(scripts.test("/static/app.js"));
+        assert!(scripts.test("/static/vendor/lib/app.js"));
+        assert!(!scripts.test("/static/app.css"));
+        assert!(!scripts.test("/assets/app.js"));
+
+        let anywhere = glob("**/*.{png,jp?g}");
+        assert!(anywhere.test("logo.png"));
+        assert!(anywhere.test("/images/photo.jpeg"));
+        assert!(!anywhere.test("/images/photo.gif"));
+
+        let segment = glob("/v[0-9]/[!_]*");
+        assert!(segment.test("/v1/users"));
+        assert!(!segment.test("/v1/_internal"));
+        assert!(!segment.test("/v1/users/42"));
+        assert!(!segment.test("/v1//"));
+
+        assert_eq!(
+            parse_glob("/a[bc", &Options::pathname()),
+            Err(GlobError::UnclosedClass(2))
+        );
+        assert_eq!(
+            parse_glob("{a,b", &Options::pathname()),
+            Err(GlobError::UnclosedAlternation(0))
+        );
+    }
+}
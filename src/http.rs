@@ -0,0 +1,256 @@
+// SPDX-License-Identifier: MIT
+
+//! Matching of [`http::Uri`] and [`http::Request`] values.
+
+use std::borrow::Cow;
+
+use ::http::header::HOST;
+use ::http::uri::Authority;
+use ::http::{Request, Uri};
+
+use crate::url_pattern::{non_default_port, UrlComponents, SPECIAL_SCHEMES};
+use crate::{UrlPattern, UrlPatternResult};
+
+/// The scheme of requests whose URI doesn't have one, like the origin-form
+/// URIs of HTTP/1.1 requests, unless the caller passes another one.
+const DEFAULT_SCHEME: &str = "http";
+
+/// Percent-encodes the bytes of `segment` in the path percent-encode set.
+///
+/// <https://url.spec.whatwg.org/#path-percent-encode-set>
+fn percent_encode_segment(segment: &str) -> String {
+    let mut output = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        if byte <= 0x20 || byte >= 0x7f || b"\"#<>?`{}".contains(&byte) {
+            output.push_str(&format!("%{byte:02X}"));
+        } else {
+            output.push(byte as char);
+        }
+    }
+    output
+}
+
+/// Removes the dot segments of `path` and percent-encodes it like the URL
+/// parser does, so that `/public/../admin` is matched as `/admin`.
+///
+/// <https://url.spec.whatwg.org/#path-state>
+fn normalize_path(path: &str, special: bool) -> Cow<'_, str> {
+    let Some(rest) = path.strip_prefix('/') else {
+        return Cow::Borrowed(path);
+    };
+
+    let separators: &[char] = if special { &['/', '\\'] } else { &['/'] };
+    let mut segments: Vec<String> = vec![];
+    let mut iter = rest.split(separators).peekable();
+    while let Some(segment) = iter.next() {
+        let last = iter.peek().is_none();
+        match segment.to_ascii_lowercase().as_str() {
+            // A double-dot segment removes the previous segment.
+            ".." | ".%2e" | "%2e." | "%2e%2e" => {
+                segments.pop();
+                if last {
+                    segments.push(String::new());
+                }
+            }
+            "." | "%2e" => {
+                if last {
+                    segments.push(String::new());
+                }
+            }
+            _ => segments.push(percent_encode_segment(segment)),
+        }
+    }
+
+    let normalized = format!("/{}", segments.join("/"));
+    if normalized == path {
+        Cow::Borrowed(path)
+    } else {
+        Cow::Owned(normalized)
+    }
+}
+
+fn exec(
+    pattern: &UrlPattern,
+    uri: &Uri,
+    fallback_scheme: &str,
+    fallback_authority: Option<&Authority>,
+) -> Option<UrlPatternResult> {
+    let protocol = uri
+        .scheme_str()
+        .unwrap_or(fallback_scheme)
+        .to_ascii_lowercase();
+
+    let authority = uri.authority().or(fallback_authority);
+    let (username, password) = authority
+        .and_then(|authority| authority.as_str().rsplit_once('@'))
+        .map(|(userinfo, _)| userinfo.split_once(':').unwrap_or((userinfo, "")))
+        .unwrap_or_default();
+    let hostname = authority.map_or(Cow::Borrowed(""), |authority| {
+        Cow::Owned(authority.host().to_ascii_lowercase())
+    });
+    let port = authority.and_then(Authority::port);
+    let port = port.as_ref().map_or("", |port| port.as_str());
+    let special = SPECIAL_SCHEMES
+        .iter()
+        .any(|&(scheme, _)| scheme == protocol);
+    let pathname = normalize_path(uri.path(), special);
+
+    pattern.exec_components(&UrlComponents {
+        protocol: &protocol,
+        username,
+        password,
+        hostname: &hostname,
+        port: non_default_port(&protocol, port),
+        pathname: &pathname,
+        search: uri.query().unwrap_or_default(),
+        hash: "",
+    })
+}
+
+impl UrlPattern {
+    /// Matches the components of an [`http::Uri`].
+    ///
+    /// A URI without a scheme is matched as `http`. A URI never has a
+    /// fragment, so the hash component is matched against the empty string.
+    ///
+    /// The path is normalized like the URL parser does: dot segments are
+    /// removed and the characters it percent-encodes are encoded, so
+    /// `/public/../admin` doesn't match `/public/*`. Percent-encoded
+    /// characters are not decoded.
+    pub fn exec_uri(&self, uri: &Uri) -> Option<UrlPatternResult> {
+        exec(self, uri, DEFAULT_SCHEME, None)
+    }
+
+    /// Returns whether an [`http::Uri`] matches.
+    pub fn test_uri(&self, uri: &Uri) -> bool {
+        self.exec_uri(uri).is_some()
+    }
+
+    /// Matches the URI of an [`http::Request`].
+    ///
+    /// The hostname and port are taken from the `Host` header if the URI
+    /// doesn't have an authority, like in most HTTP/1.1 requests. A URI
+    /// without a scheme is matched as `http`, see
+    /// [`UrlPattern::exec_request_with_scheme`] for requests received over TLS.
+    /// The path is normalized like in [`UrlPattern::exec_uri`].
+    pub fn exec_request<B>(&self, request: &Request<B>) -> Option<UrlPatternResult> {
+        self.exec_request_with_scheme(request, DEFAULT_SCHEME)
+    }
+
+    /// Returns whether the URI of an [`http::Request`] matches.
+    pub fn test_request<B>(&self, request: &Request<B>) -> bool {
+        self.exec_request(request).is_some()
+    }
+
+    /// Like [`UrlPattern::exec_request`], but matches a URI without a scheme
+    /// as `scheme`.
+    ///
+    /// A server knows the scheme from the listener a request was received on,
+    /// while the request itself usually doesn't carry it.
+    pub fn exec_request_with_scheme<B>(
+        &self,
+        request: &Request<B>,
+        scheme: &str,
+    ) -> Option<UrlPatternResult> {
+        let host = request
+            .headers()
+            .get(HOST)
+            .and_then(|host| Authority::try_from(host.as_bytes()).ok());
+        exec(self, request.uri(), scheme, host.as_ref())
+    }
+
+    /// Returns whether the URI of an [`http::Request`] matches, with `scheme`
+    /// for a URI without one.
+    pub fn test_request_with_scheme<B>(&self, request: &Request<B>, scheme: &str) -> bool {
+        self.exec_request_with_scheme(request, scheme).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::http::{Request, Uri};
+
+    use crate::{UrlPattern, UrlPatternOptions};
+
+    #[test]
+    fn uri_and_request() {
+        let pattern = UrlPattern::parse(
+            "http{s}?://:sub.example.com/users/:id",
+            None,
+            &UrlPatternOptions::default(),
+        )
+        .unwrap();
+
+        let uri: Uri = "https://API.example.com:443/users/42?tab=files"
+            .parse()
+            .unwrap();
+        let result = pattern.exec_uri(&uri).unwrap();
+        assert_eq!(result.hostname.groups["sub"].as_deref(), Some("api"));
+        assert_eq!(result.pathname.groups["id"].as_deref(), Some("42"));
+        assert_eq!(result.search.input, "tab=files");
+        assert!(!pattern.test_uri(&"https://api.example.com:8443/users/42".parse().unwrap()));
+
+        let request = Request::get("/users/7")
+            .header("host", "www.example.com")
+            .body(())
+            .unwrap();
+        let result = pattern.exec_request(&request).unwrap();
+        assert_eq!(result.protocol.input, "http");
+        assert_eq!(result.hostname.groups["sub"].as_deref(), Some("www"));
+
+        let request = Request::get("/users/7").body(()).unwrap();
+        assert!(!pattern.test_request(&request));
+
+        // Origin-form requests on a TLS listener.
+        let pattern = UrlPattern::parse(
+            "https://example.com/users/:id",
+            None,
+            &UrlPatternOptions::default(),
+        )
+        .unwrap();
+        let request = Request::get("/users/7")
+            .header("host", "example.com:443")
+            .body(())
+            .unwrap();
+        assert!(!pattern.test_request(&request));
+        let result = pattern.exec_request_with_scheme(&request, "HTTPS").unwrap();
+        assert_eq!(result.protocol.input, "https");
+        assert_eq!(result.port.input, "");
+        assert!(pattern.test_request_with_scheme(&request, "https"));
+
+        // The scheme of an absolute URI wins.
+        let request = Request::get("http://example.com/users/7").body(()).unwrap();
+        assert!(!pattern.test_request_with_scheme(&request, "https"));
+    }
+
+    #[test]
+    fn path_normalization() {
+        let pattern = UrlPattern::parse(
+            "https://example.com/public/*",
+            None,
+            &UrlPatternOptions::default(),
+        )
+        .unwrap();
+        let exec = |uri: &str| pattern.exec_uri(&uri.parse::<Uri>().unwrap());
+
+        assert!(exec("https://example.com/public/../admin").is_none());
+        assert!(exec("https://example.com/public/%2E%2e/admin").is_none());
+        let result = exec("https://example.com/public/./a/b/../c/..").unwrap();
+        assert_eq!(result.pathname.input, "/public/a/");
+        let result = exec("https://example.com/public/x/../{a}%20b").unwrap();
+        assert_eq!(result.pathname.input, "/public/%7Ba%7D%20b");
+
+        let request = Request::get("/public/../admin")
+            .header("host", "example.com")
+            .body(())
+            .unwrap();
+        assert!(!pattern.test_request_with_scheme(&request, "https"));
+        let admin = UrlPattern::parse(
+            "https://example.com/admin",
+            None,
+            &UrlPatternOptions::default(),
+        )
+        .unwrap();
+        assert!(admin.test_request_with_scheme(&request, "https"));
+    }
+}
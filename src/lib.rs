@@ -3,72 +3,251 @@
 /*!
 url-pattern is a VERY INCOMPLETE implementation of the WHATWG [URL Pattern](https://urlpattern.spec.whatwg.org/) standard.
 Seriously **DON'T USE THIS** (yet)!
+
+Without the default `regex` feature the crate is `no_std` and only needs
+`alloc`. Patterns can then be parsed with [`parse_pattern`] and turned into
+regular expressions with [`regexp_for_pattern`] or [`regexp_for_parts`], but
+nothing matches against them.
 */
 
+#![cfg_attr(not(any(feature = "regex", test)), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "regex")]
+mod automaton;
+#[cfg(feature = "regex")]
+mod cache;
+#[cfg(feature = "regex")]
+mod component;
+mod constraint;
+#[cfg(feature = "regex")]
+mod constructor_parser;
+mod dialect;
+#[cfg(feature = "regex")]
+mod examples;
+#[cfg(feature = "regex")]
+mod explain;
+#[cfg(feature = "regex")]
+mod extract;
+#[cfg(feature = "ffi")]
+mod ffi;
+#[cfg(feature = "regex")]
+mod glob;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "regex")]
+mod match_pattern;
+#[cfg(feature = "regex")]
+mod openapi;
 mod parser;
+#[cfg(feature = "regex")]
+mod path_to_regexp;
+#[cfg(feature = "regex")]
+mod search;
+#[cfg(feature = "serde")]
+mod serialization;
 mod tokenizer;
+#[cfg(feature = "tower")]
+mod tower;
+#[cfg(feature = "regex")]
+mod uri_template;
+#[cfg(feature = "url")]
+mod url;
+#[cfg(feature = "regex")]
+mod url_pattern;
 
-use crate::parser::{Modifier, Parser, Part};
-use crate::tokenizer::{tokenize, Policy};
+#[cfg(feature = "regex")]
+pub use crate::automaton::AnalysisError;
+#[cfg(feature = "regex")]
+pub use crate::cache::{
+    read_components, read_url_patterns, write_components, write_url_patterns, CacheError,
+    CACHE_FORMAT_VERSION,
+};
+#[cfg(feature = "regex")]
+pub use crate::component::{Component, ComponentResult};
+pub use crate::constraint::{Constraint, Constraints, Validate};
+pub use crate::dialect::{DialectError, RegexDialect};
+#[cfg(feature = "regex")]
+pub use crate::explain::Explanation;
+#[cfg(feature = "regex")]
+pub use crate::extract::{ExtractError, FromUrlPattern, GroupKind, Groups};
+#[cfg(feature = "regex")]
+pub use crate::glob::{parse_glob, GlobError};
+#[cfg(feature = "regex")]
+pub use crate::match_pattern::{from_match_pattern, MatchPatternError};
+#[cfg(feature = "regex")]
+pub use crate::openapi::{
+    openapi_path, OpenApiError, OpenApiParameter, OpenApiPath, OpenApiSchema,
+};
+pub use crate::parser::{Modifier, Part};
+#[cfg(feature = "regex")]
+pub use crate::path_to_regexp::{
+    import_path_to_regexp, ImportError, ImportWarning, ImportedRoute, PathToRegexpVersion,
+};
+#[cfg(feature = "regex")]
+pub use crate::search::{SearchPattern, SearchPatternError, SearchPatternOptions};
+#[cfg(feature = "tower")]
+pub use crate::tower::{PatternRouter, PatternRouterLayer};
+#[cfg(feature = "regex")]
+pub use crate::uri_template::{
+    from_uri_template, to_uri_template, UriTemplateError, UriTemplateParts, UriTemplateReport,
+};
+#[cfg(feature = "regex")]
+pub use crate::url_pattern::{
+    UrlPattern, UrlPatternError, UrlPatternInit, UrlPatternOptions, UrlPatternResult,
+};
 
-use thiserror::Error;
+use crate::parser::Parser;
+use crate::tokenizer::{is_valid_name_code_point, tokenize, Policy};
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
 
 /// <https://urlpattern.spec.whatwg.org/#options>
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Debug)]
 pub struct Options {
     pub delimiter: Option<char>,
     pub prefix: Option<char>,
     pub ignore_case: bool,
+    /// The constraints names can refer to, like `:id<int>`.
+    ///
+    /// With at least one registered constraint, a `<` right after a name
+    /// starts a constraint instead of being fixed text, so `:id<x` doesn't
+    /// parse anymore. Write `{:id}<x` or `:id\<x` for the fixed text.
+    pub constraints: Constraints,
+}
+
+impl Options {
+    /// <https://urlpattern.spec.whatwg.org/#hostname-options>
+    pub fn hostname() -> Options {
+        Options {
+            delimiter: Some('.'),
+            ..Default::default()
+        }
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#pathname-options>
+    pub fn pathname() -> Options {
+        Options {
+            delimiter: Some('/'),
+            prefix: Some('/'),
+            ..Default::default()
+        }
+    }
 }
 
-#[derive(Error, Debug)]
+#[derive(Debug)]
+#[non_exhaustive]
 pub enum ParseError {
-    #[error("unexpected end of pattern reached")]
     UnexpectedEnd,
-    #[error("missing one or more closing parentheses `)` in regular expression")]
     ParenthesesMissmatch,
-    #[error("missing closing curly brackets `}}`")]
-    MissingClosingCurly
+    MissingClosingCurly,
+    TrailingBackslash,
+    MissingName,
+    NonAsciiRegExp,
+    InvalidRegExpStart,
+    CapturingGroupInRegExp,
+    EmptyRegExp,
+    DuplicateName(String),
+    #[cfg(feature = "regex")]
+    InvalidRegExp(regex::Error),
+    UnknownConstraint(String),
+    UnclosedConstraint,
+    InvalidConstraint(String),
 }
 
-/// <https://urlpattern.spec.whatwg.org/#generate-a-segment-wildcard-regexp>
-fn generate_segment_wildcard_regexp(opts: &Options) -> String {
-    format!(
-        "[^{}]+?",
-        escape_regexp(
-            &opts
-                .delimiter
-                .map_or_else(|| String::new(), |chr| chr.to_string())
-        )
-    )
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedEnd => write!(f, "unexpected end of pattern reached"),
+            ParseError::ParenthesesMissmatch => write!(
+                f,
+                "missing one or more closing parentheses `)` in regular expression"
+            ),
+            ParseError::MissingClosingCurly => write!(f, "missing closing curly brackets `}}`"),
+            ParseError::TrailingBackslash => {
+                write!(f, "trailing backslash `\\` without a character to escape")
+            }
+            ParseError::MissingName => write!(f, "missing name after `:`"),
+            ParseError::NonAsciiRegExp => {
+                write!(f, "regular expression contains a non-ASCII character")
+            }
+            ParseError::InvalidRegExpStart => {
+                write!(f, "regular expression must not start with `?`")
+            }
+            ParseError::CapturingGroupInRegExp => write!(
+                f,
+                "regular expression contains a capturing group, use `(?:` instead"
+            ),
+            ParseError::EmptyRegExp => write!(f, "regular expression is empty"),
+            ParseError::DuplicateName(name) => write!(f, "duplicate name `{name}`"),
+            #[cfg(feature = "regex")]
+            ParseError::InvalidRegExp(error) => write!(f, "invalid regular expression: {error}"),
+            ParseError::UnknownConstraint(name) => write!(f, "unknown constraint `{name}`"),
+            ParseError::UnclosedConstraint => {
+                write!(f, "missing closing `>` after the constraint name")
+            }
+            ParseError::InvalidConstraint(regexp) => {
+                write!(f, "{regexp:?} is not a valid constraint regexp")
+            }
+        }
+    }
 }
 
-/// <https://urlpattern.spec.whatwg.org/#full-wildcard-regexp-value>
-fn full_wildcard_regexp() -> &'static str {
-    ".*"
+impl core::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "regex")]
+            ParseError::InvalidRegExp(error) => Some(error),
+            _ => None,
+        }
+    }
 }
 
-fn escape_regexp(str: &str) -> String {
-    // TODO:
-    str.replace("/", "\\/")
+#[cfg(feature = "regex")]
+impl From<regex::Error> for ParseError {
+    fn from(error: regex::Error) -> Self {
+        ParseError::InvalidRegExp(error)
+    }
+}
+
+/// <https://urlpattern.spec.whatwg.org/#full-wildcard-regexp-value>
+pub(crate) fn full_wildcard_regexp() -> &'static str {
+    ".*"
 }
 
 /// <https://urlpattern.spec.whatwg.org/#generate-a-regular-expression-and-name-list>
-fn generate_regexp(parts: &[Part], opts: &Options) -> String {
+fn generate_regexp(
+    parts: &[Part],
+    opts: &Options,
+    dialect: RegexDialect,
+) -> Result<(String, Vec<String>, Vec<usize>), DialectError> {
     let mut result: String = "^".into();
+    let mut names = vec![];
+    // The capture group index of every name, and the number of groups so far.
+    let mut indices = vec![];
+    let mut groups = 0;
+    let group = dialect.non_capturing_group();
+    let extra = usize::from(dialect.groups_capture());
+
+    if opts.ignore_case {
+        result.insert_str(0, dialect.case_insensitive()?);
+    }
 
     for part in parts {
-        let (_, modifier, regexp, prefix, suffix) = match part {
+        let (name, modifier, regexp, prefix, suffix) = match part {
             // If part’s type is "fixed-text":
             Part::FixedText { value, modifier } => {
-                result.push_str(
-                    if let Some(modifier) = modifier {
-                        format!("(?:{}){modifier}", &escape_regexp(value))
-                    } else {
-                        escape_regexp(value)
-                    }
-                    .as_ref(),
-                );
+                if let Some(modifier) = modifier {
+                    groups += extra;
+                    result.push_str(&format!("{group}{}){modifier}", dialect.escape(value)));
+                } else {
+                    result.push_str(&dialect.escape(value));
+                }
                 continue;
             }
             Part::SegmentWildcard {
@@ -79,9 +258,9 @@ fn generate_regexp(parts: &[Part], opts: &Options) -> String {
             } => (
                 name,
                 modifier,
-                generate_segment_wildcard_regexp(opts),
-                escape_regexp(prefix),
-                escape_regexp(suffix),
+                dialect.segment_wildcard(opts.delimiter),
+                dialect.escape(prefix),
+                dialect.escape(suffix),
             ),
             Part::FullWildcard {
                 name,
@@ -92,8 +271,8 @@ fn generate_regexp(parts: &[Part], opts: &Options) -> String {
                 name,
                 modifier,
                 full_wildcard_regexp().into(),
-                escape_regexp(prefix),
-                escape_regexp(suffix),
+                dialect.escape(prefix),
+                dialect.escape(suffix),
             ),
             Part::RegExp {
                 name,
@@ -101,40 +280,54 @@ fn generate_regexp(parts: &[Part], opts: &Options) -> String {
                 modifier,
                 prefix,
                 suffix,
+                ..
             } => (
                 name,
                 modifier,
                 value.clone(),
-                escape_regexp(prefix),
-                escape_regexp(suffix),
+                dialect.escape(prefix),
+                dialect.escape(suffix),
             ),
         };
 
+        // Append part’s name to name list.
+        names.push(name.clone());
+
         // If part’s prefix is the empty string and part’s suffix is the empty string:
         // ..
         if prefix.is_empty() && suffix.is_empty() {
+            indices.push(groups + 1);
             result.push_str(
                 match modifier {
                     None => format!("({regexp})"),
                     Some(modifier @ Modifier::Optional) => format!("({regexp}){modifier}"),
-                    Some(modifier) => format!("((?:{regexp}){modifier})"),
+                    Some(modifier) => {
+                        groups += extra;
+                        format!("({group}{regexp}){modifier})")
+                    }
                 }
                 .as_ref(),
             );
+            groups += 1;
             continue;
         }
 
+        indices.push(groups + extra + 1);
+        groups += match modifier {
+            None | Some(Modifier::Optional) => 1 + extra,
+            Some(Modifier::ZeroOrMore | Modifier::OneOrMore) => 1 + 4 * extra,
+        };
         result.push_str(
             match modifier {
-                None => format!("(?:{prefix}({regexp}){suffix})"),
+                None => format!("{group}{prefix}({regexp}){suffix})"),
                 Some(modifier @ Modifier::Optional) => {
-                    format!("(?:{prefix}({regexp}){suffix}){modifier}")
+                    format!("{group}{prefix}({regexp}){suffix}){modifier}")
                 }
                 Some(Modifier::ZeroOrMore) => {
-                    format!("(?:{prefix}((?:{regexp})(?:{suffix}{prefix}(?:{regexp}))*){suffix})?")
+                    format!("{group}{prefix}({group}{regexp}){group}{suffix}{prefix}{group}{regexp}))*){suffix})?")
                 }
                 Some(Modifier::OneOrMore) => {
-                    format!("(?:{prefix}((?:{regexp})(?:{suffix}{prefix}(?:{regexp}))*){suffix})")
+                    format!("{group}{prefix}({group}{regexp}){group}{suffix}{prefix}{group}{regexp}))*){suffix})")
                 }
             }
             .as_ref(),
@@ -142,31 +335,264 @@ fn generate_regexp(parts: &[Part], opts: &Options) -> String {
     }
 
     result.push('$');
+    Ok((result, names, indices))
+}
+
+/// <https://urlpattern.spec.whatwg.org/#escape-a-pattern-string>
+#[cfg_attr(not(feature = "regex"), allow(dead_code))]
+fn escape_pattern_string(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    for chr in input.chars() {
+        if "+*?:{}()\\".contains(chr) {
+            result.push('\\');
+        }
+        result.push(chr);
+    }
+    result
+}
+
+/// Returns whether `text` would start a constraint right after a name.
+fn starts_constraint(text: &str, opts: &Options) -> bool {
+    !opts.constraints.is_empty() && text.starts_with('<')
+}
+
+/// <https://urlpattern.spec.whatwg.org/#generate-a-pattern-string>
+#[cfg_attr(not(feature = "regex"), allow(dead_code))]
+fn generate_pattern_string(parts: &[Part], opts: &Options) -> String {
+    let mut result = String::new();
+
+    for (index, part) in parts.iter().enumerate() {
+        // Let previous part be part list[index - 1] if index is greater than 0, otherwise let it be null.
+        let previous = index.checked_sub(1).map(|index| &parts[index]);
+        // Let next part be part list[index + 1] if index is less than index list’s size - 1, otherwise let it be null.
+        let next = parts.get(index + 1);
+
+        let (name, modifier, prefix, suffix) = match part {
+            // If part’s type is "fixed-text" then:
+            Part::FixedText { value, modifier } => {
+                match modifier {
+                    None => result.push_str(&escape_pattern_string(value)),
+                    Some(modifier) => {
+                        result.push_str(&format!("{{{}}}{modifier}", escape_pattern_string(value)))
+                    }
+                }
+                continue;
+            }
+            Part::SegmentWildcard {
+                name,
+                modifier,
+                prefix,
+                suffix,
+            }
+            | Part::FullWildcard {
+                name,
+                modifier,
+                prefix,
+                suffix,
+            }
+            | Part::RegExp {
+                name,
+                modifier,
+                prefix,
+                suffix,
+                ..
+            } => (name, modifier, prefix, suffix),
+        };
+
+        // Let custom name be true if part’s name[0] is not an ASCII digit; otherwise false.
+        let custom_name = !name.starts_with(|chr: char| chr.is_ascii_digit());
+
+        // Let needs grouping be true if at least one of the following are true, otherwise let it be false:
+        // - part’s suffix is not the empty string.
+        // - part’s prefix is not the empty string and is not options’s prefix code point.
+        let mut needs_grouping = !suffix.is_empty()
+            || (!prefix.is_empty() && opts.prefix.map(String::from).as_ref() != Some(prefix));
+
+        // If all of the following are true: ..
+        if !needs_grouping
+            && custom_name
+            && matches!(part, Part::SegmentWildcard { modifier: None, .. })
+        {
+            match next {
+                Some(Part::FixedText { value, .. }) => {
+                    // Set needs grouping to true if the result of running is a valid name code point given next part’s value’s first code point and the boolean false is true.
+                    needs_grouping = value
+                        .chars()
+                        .next()
+                        .is_some_and(|chr| is_valid_name_code_point(chr, false))
+                        || starts_constraint(value, opts);
+                }
+                Some(
+                    Part::SegmentWildcard {
+                        name,
+                        prefix,
+                        suffix,
+                        ..
+                    }
+                    | Part::FullWildcard {
+                        name,
+                        prefix,
+                        suffix,
+                        ..
+                    }
+                    | Part::RegExp {
+                        name,
+                        prefix,
+                        suffix,
+                        ..
+                    },
+                ) if prefix.is_empty() && suffix.is_empty() => {
+                    // Set needs grouping to true if next part’s name[0] is an ASCII digit.
+                    needs_grouping = name.starts_with(|chr: char| chr.is_ascii_digit());
+                }
+                _ => {}
+            }
+        }
+
+        // If all of the following are true: ..
+        if !needs_grouping && prefix.is_empty() {
+            if let (Some(Part::FixedText { value, .. }), Some(opt_prefix)) = (previous, opts.prefix)
+            {
+                needs_grouping = value.ends_with(opt_prefix);
+            }
+        }
+
+        if needs_grouping {
+            result.push('{');
+        }
+
+        result.push_str(&escape_pattern_string(prefix));
+
+        if custom_name {
+            result.push(':');
+            result.push_str(name);
+        }
+
+        match part {
+            // A constraint is written by its name, which parses back into the same part.
+            Part::RegExp {
+                constraint: Some(constraint),
+                ..
+            } if custom_name => result.push_str(&format!("<{constraint}>")),
+            // If part’s type is "regexp" then:
+            Part::RegExp { value, .. } => result.push_str(&format!("({value})")),
+            // Otherwise if part’s type is "segment-wildcard" and custom name is false:
+            Part::SegmentWildcard { .. } if !custom_name => {
+                let regexp = RegexDialect::EcmaScript.segment_wildcard(opts.delimiter);
+                result.push_str(&format!("({regexp})"));
+            }
+            // Otherwise if part’s type is "full-wildcard":
+            Part::FullWildcard { .. } => {
+                let previous_is_plain = matches!(
+                    previous,
+                    Some(
+                        Part::SegmentWildcard { modifier: None, .. }
+                            | Part::FullWildcard { modifier: None, .. }
+                            | Part::RegExp { modifier: None, .. }
+                    )
+                );
+                if !custom_name && (!previous_is_plain || needs_grouping || !prefix.is_empty()) {
+                    result.push('*');
+                } else {
+                    result.push_str(&format!("({})", full_wildcard_regexp()));
+                }
+            }
+            _ => {}
+        }
+
+        // If all of the following are true: ..
+        if matches!(part, Part::SegmentWildcard { .. })
+            && custom_name
+            && (suffix
+                .chars()
+                .next()
+                .is_some_and(|chr| is_valid_name_code_point(chr, false))
+                || starts_constraint(suffix, opts))
+        {
+            result.push('\\');
+        }
+
+        result.push_str(&escape_pattern_string(suffix));
+
+        if needs_grouping {
+            result.push('}');
+        }
+
+        if let Some(modifier) = modifier {
+            result.push_str(&modifier.to_string());
+        }
+    }
+
     result
 }
 
+/// Parses a pattern string into its list of parts.
+///
+/// <https://urlpattern.spec.whatwg.org/#parse-a-pattern-string>
+pub fn parse_pattern(input: &str, options: &Options) -> Result<Vec<Part>, ParseError> {
+    let tokens = tokenize(input, Policy::Strict, !options.constraints.is_empty())?;
+
+    let mut parser = Parser::new(&tokens, options);
+    parser.parse()?;
+
+    Ok(parser.parts)
+}
+
+/// The byte offset in `input` at which parsing it as a pattern fails, if it
+/// does.
+#[cfg(feature = "regex")]
+pub(crate) fn error_position(input: &str, options: &Options) -> Option<usize> {
+    // The lenient policy turns tokenizing errors into invalid-char tokens at
+    // the position of the error.
+    let tokens = tokenize(input, Policy::Lenient, !options.constraints.is_empty()).ok()?;
+    if let Some(token) = tokens
+        .iter()
+        .find(|token| matches!(token.kind, tokenizer::TokenKind::InvalidChar(_)))
+    {
+        return Some(token.span.start);
+    }
+
+    let mut parser = Parser::new(&tokens, options);
+    let error = parser.parse().err()?;
+    Some(parser.error_position(&error))
+}
+
 /// Parses a pattern string and returns a regular expression for matching that
 /// pattern.
 pub fn regexp_for_pattern(input: &str, options: &Options) -> Result<String, ParseError> {
-    let tokens = tokenize(input, Policy::Strict)?;
+    let parts = parse_pattern(input, options)?;
 
-    let mut parser = Parser::new(&tokens, options);
-    parser.parse()?;
+    let (regexp, ..) = generate_regexp(&parts, options, RegexDialect::EcmaScript)
+        .expect("ECMAScript supports every pattern");
+    Ok(regexp)
+}
 
-    Ok(generate_regexp(&parser.parts, options))
+/// Generates the regular expression for a list of parts in `dialect`, and the
+/// names of its capturing groups with the index of their group.
+///
+/// <https://urlpattern.spec.whatwg.org/#generate-a-regular-expression-and-name-list>
+pub fn regexp_for_parts(
+    parts: &[Part],
+    options: &Options,
+    dialect: RegexDialect,
+) -> Result<(String, Vec<(String, usize)>), DialectError> {
+    let (regexp, names, indices) = generate_regexp(parts, options, dialect)?;
+    Ok((regexp, names.into_iter().zip(indices).collect()))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    // TODO: Verify that of all these results are correct!!
+    // The expected regexps follow
+    // https://urlpattern.spec.whatwg.org/#generate-a-regular-expression-and-name-list.
 
     fn test_path(pattern: &str, expected: &str) {
         let opts = Options {
             delimiter: Some('/'),
             prefix: Some('/'),
             ignore_case: false,
+            ..Default::default()
         };
 
         let result = regexp_for_pattern(pattern, &opts).unwrap();
@@ -186,6 +612,34 @@ mod tests {
         test_path("/:foo{/}?", r"^(?:\/([^\/]+?))(?:\/)?$");
     }
 
+    #[test]
+    fn tokenize_and_parse() {
+        test_path(r"/\:foo", r"^\/:foo$");
+        test_path("/:café/:$_x1", r"^(?:\/([^\/]+?))(?:\/([^\/]+?))$");
+        test_path(r"/(a(?:b)\))/(\d+)", r"^(?:\/(a(?:b)\)))(?:\/(\d+))$");
+
+        let error = |pattern| {
+            regexp_for_pattern(pattern, &Options::default())
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(error("a\\"), ParseError::TrailingBackslash.to_string());
+        assert_eq!(error("/:"), ParseError::MissingName.to_string());
+        assert_eq!(error("/:0"), ParseError::MissingName.to_string());
+        assert_eq!(error("(é)"), ParseError::NonAsciiRegExp.to_string());
+        assert_eq!(error("(?:a)"), ParseError::InvalidRegExpStart.to_string());
+        assert_eq!(
+            error("(a(b))"),
+            ParseError::CapturingGroupInRegExp.to_string()
+        );
+        assert_eq!(error("()"), ParseError::EmptyRegExp.to_string());
+        assert_eq!(error("(a"), ParseError::ParenthesesMissmatch.to_string());
+        assert_eq!(
+            error("/:foo/:foo"),
+            ParseError::DuplicateName("foo".into()).to_string()
+        );
+    }
+
     #[test]
     fn parse_example_1() {
         // From https://urlpattern.spec.whatwg.org/#parse-example-1
@@ -199,6 +653,16 @@ mod tests {
         test_path("/(bar)?", r"^(?:\/(bar))?$");
     }
 
+    #[test]
+    fn prefix_without_prefix_code_point() {
+        // Without a prefix code point, the text before a name is fixed text
+        // and not the name's prefix.
+        let hostname = |pattern| regexp_for_pattern(pattern, &Options::hostname()).unwrap();
+        assert_eq!(hostname("a:foo"), r"^a([^\.]+?)$");
+        assert_eq!(hostname("{a:foo}?"), r"^(?:a([^\.]+?))?$");
+        assert_eq!(hostname(".:foo"), r"^\.([^\.]+?)$");
+    }
+
     #[test]
     fn parse_example_2() {
         // From https://urlpattern.spec.whatwg.org/#parsing-example-2
@@ -207,4 +671,97 @@ mod tests {
         test_path("{(bar)}?", "^(bar)?$");
         test_path("{ab}?", r"^(?:ab)?$");
     }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn component_exec() {
+        let component = Component::compile(r"/:foo/(\d+)/*?", &Options::pathname()).unwrap();
+        assert_eq!(component.group_names(), ["foo", "0", "1"]);
+
+        let result = component.exec("/abc/12").unwrap();
+        assert_eq!(result.groups["foo"].as_deref(), Some("abc"));
+        assert_eq!(result.groups["0"].as_deref(), Some("12"));
+        assert_eq!(result.groups["1"], None);
+
+        assert!(component.test("/abc/12/x/y"));
+        assert!(!component.test("/abc/def"));
+
+        assert!(matches!(
+            parse_pattern("/:foo/:foo", &Options::pathname()),
+            Err(ParseError::DuplicateName(_))
+        ));
+        assert!(matches!(
+            parse_pattern("/(a(b))", &Options::pathname()),
+            Err(ParseError::CapturingGroupInRegExp)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn dialects() {
+        let component = Component::compile("/:foo/a.b{-:bar}?", &Options::pathname()).unwrap();
+        let regexp = |dialect| component.regexp_for(dialect);
+
+        let ecmascript = r"^(?:\/([^\/]+?))\/a\.b(?:-([^\/]+?))?$";
+        assert_eq!(regexp(RegexDialect::EcmaScript).unwrap(), ecmascript);
+        assert_eq!(
+            regexp(RegexDialect::EcmaScriptUnicodeSets).unwrap(),
+            ecmascript
+        );
+        assert_eq!(
+            regexp(RegexDialect::Rust).unwrap(),
+            r"^(?:/([^/]+?))/a\.b(?:\-([^/]+?))?$"
+        );
+        assert_eq!(
+            regexp(RegexDialect::Re2).unwrap(),
+            r"^(?:/([^/]+?))/a\.b(?:-([^/]+?))?$"
+        );
+        assert_eq!(
+            regexp(RegexDialect::PosixExtended).unwrap(),
+            r"^(/([^/]+))/a\.b(-([^/]+))?$"
+        );
+        assert_eq!(
+            component.group_indices_for(RegexDialect::PosixExtended),
+            Ok(vec![2, 4])
+        );
+        assert_eq!(
+            component.group_indices_for(RegexDialect::Rust),
+            Ok(vec![1, 2])
+        );
+
+        // Only the ERE special characters are escaped, and every group captures.
+        let component = Component::compile(r"/{a}?]\}*/:b+", &Options::pathname()).unwrap();
+        assert_eq!(
+            component.regexp_for(RegexDialect::PosixExtended).unwrap(),
+            r"^/(a)?]}(.*)(/(([^/]+)(/([^/]+))*))$"
+        );
+        assert_eq!(
+            component.group_indices_for(RegexDialect::PosixExtended),
+            Ok(vec![2, 4])
+        );
+        assert_eq!(
+            Component::compile("/:a", &Options::pathname())
+                .unwrap()
+                .regexp_for(RegexDialect::PosixExtended),
+            Ok(r"^(/([^/]+))$".into())
+        );
+        let options = Options {
+            ignore_case: true,
+            ..Options::pathname()
+        };
+        assert_eq!(
+            Component::compile("/a", &options)
+                .unwrap()
+                .regexp_for(RegexDialect::PosixExtended),
+            Err(DialectError::CaseInsensitive(RegexDialect::PosixExtended))
+        );
+
+        let component = Component::compile(":foo", &Options::default()).unwrap();
+        assert_eq!(
+            component.regexp_for(RegexDialect::EcmaScript).unwrap(),
+            "^([^]+?)$"
+        );
+        assert_eq!(component.regexp(), "^((?s:.)+?)$");
+        assert!(component.test("a/b"));
+    }
 }
@@ -0,0 +1,350 @@
+// SPDX-License-Identifier: MIT
+
+//! Command-line tool for compiling and testing URL patterns.
+
+use std::env;
+use std::process::ExitCode;
+
+use url_pattern::{
+    Component, DialectError, Options, ParseError, Part, RegexDialect, UrlPattern, UrlPatternError,
+    UrlPatternInit, UrlPatternOptions,
+};
+
+const USAGE: &str = "\
+Usage: url-pattern [OPTIONS] <COMMAND>
+
+Commands:
+  compile <PATTERN>          Print the normalized pattern, regular expression and group names
+  test <PATTERN> <URL>       Print whether URL matches and the captured groups
+  explain <PATTERN> [URL]    Print the parts of the pattern and which part rejects URL
+
+Options:
+  --options <KIND>           One of `pathname` (default), `hostname` or `default`
+  --dialect <DIALECT>        Regular expression syntax printed by `compile`, one of
+                             `ecmascript` (default), `ecmascript-v`, `rust`, `pcre2`,
+                             `re2` or `posix-ere`
+  --base-url <URL>           Base URL for a relative PATTERN or URL given to `test`
+  --ignore-case              Match case-insensitively
+  -h, --help                 Print this help
+
+`test` and `explain` match a whole URL against a constructor string like
+`https://*.example.com/users/:id`. `compile` works on the pattern of a single
+URL component, chosen with `--options`.
+
+Exit status: 0 on success or a match, 1 if the input doesn't match, 2 for a
+usage error or an invalid pattern, and 3 for an invalid URL.";
+
+enum Error {
+    Usage(String),
+    Pattern(ParseError),
+    UrlPattern(UrlPatternError),
+    Dialect(DialectError),
+    Url(String),
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Usage(message)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Error::Usage(message.into())
+    }
+}
+
+fn main() -> ExitCode {
+    match run(env::args().skip(1).collect()) {
+        Ok(code) => code,
+        Err(Error::Usage(message)) => {
+            eprintln!("error: {message}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+        Err(Error::Pattern(err)) => {
+            eprintln!("error: invalid pattern: {err}");
+            ExitCode::from(2)
+        }
+        Err(Error::UrlPattern(err)) => {
+            eprintln!("error: invalid pattern: {err}");
+            ExitCode::from(2)
+        }
+        Err(Error::Dialect(err)) => {
+            eprintln!("error: {err}");
+            ExitCode::from(2)
+        }
+        Err(Error::Url(url)) => {
+            eprintln!("error: invalid URL {url:?}");
+            ExitCode::from(3)
+        }
+    }
+}
+
+/// Splits `url` into its components, resolving it against `base_url` if it
+/// is relative.
+fn parse_url(url: &str, base_url: Option<&str>) -> Option<UrlPatternInit> {
+    if let Some(init) = UrlPatternInit::from_url(url) {
+        return Some(init);
+    }
+
+    let base_url = base_url?;
+    let base = UrlPatternInit::from_url(base_url)?;
+    if let Some(rest) = url.strip_prefix("//") {
+        return UrlPatternInit::from_url(&format!("{}://{rest}", base.protocol?));
+    }
+
+    // The components the URL has replace those of the base URL, like the
+    // processing of a `UrlPatternInit` with a base URL does.
+    let (rest, hash) = match url.split_once('#') {
+        Some((rest, hash)) => (rest, Some(hash.to_string())),
+        None => (url, None),
+    };
+    let (pathname, search) = match rest.split_once('?') {
+        Some((pathname, search)) => (pathname, Some(search.to_string())),
+        None => (rest, None),
+    };
+    Some(UrlPatternInit {
+        pathname: (!pathname.is_empty()).then(|| pathname.to_string()),
+        search,
+        hash,
+        base_url: Some(base_url.into()),
+        ..Default::default()
+    })
+}
+
+fn run(args: Vec<String>) -> Result<ExitCode, Error> {
+    let mut options = Options::pathname();
+    let mut ignore_case = false;
+    let mut dialect = RegexDialect::EcmaScript;
+    let mut base_url = None;
+    let mut positional = vec![];
+
+    let mut iter = args.into_iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(ExitCode::SUCCESS);
+            }
+            "--ignore-case" => ignore_case = true,
+            "--base-url" => match iter.next() {
+                Some(url) => base_url = Some(url),
+                None => return Err("missing value for `--base-url`".into()),
+            },
+            "--options" => {
+                options = match iter.next().as_deref() {
+                    Some("pathname") => Options::pathname(),
+                    Some("hostname") => Options::hostname(),
+                    Some("default") => Options::default(),
+                    Some(kind) => return Err(format!("unknown options `{kind}`").into()),
+                    None => return Err("missing value for `--options`".into()),
+                }
+            }
+            "--dialect" => {
+                dialect = match iter.next().as_deref() {
+                    Some("ecmascript") => RegexDialect::EcmaScript,
+                    Some("ecmascript-v") => RegexDialect::EcmaScriptUnicodeSets,
+                    Some("rust") => RegexDialect::Rust,
+                    Some("pcre2") => RegexDialect::Pcre2,
+                    Some("re2") => RegexDialect::Re2,
+                    Some("posix-ere") => RegexDialect::PosixExtended,
+                    Some(name) => return Err(format!("unknown dialect `{name}`").into()),
+                    None => return Err("missing value for `--dialect`".into()),
+                }
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option `{arg}`").into()),
+            _ => positional.push(arg),
+        }
+    }
+    options.ignore_case = ignore_case;
+
+    let compile = |pattern: &str| Component::compile(pattern, &options).map_err(Error::Pattern);
+    let parse = |pattern: &str| {
+        UrlPattern::parse(
+            pattern,
+            base_url.as_deref(),
+            &UrlPatternOptions { ignore_case },
+        )
+        .map_err(Error::UrlPattern)
+    };
+
+    match positional
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["compile", pattern] => {
+            let component = compile(pattern)?;
+            let regexp = component.regexp_for(dialect).map_err(Error::Dialect)?;
+            println!("pattern: {}", component.pattern_string());
+            println!("regexp: {regexp}");
+            let flags = dialect.flags(ignore_case);
+            if !flags.is_empty() {
+                println!("flags: {flags}");
+            }
+            println!("groups: {}", component.group_names().join(", "));
+            let indices = component
+                .group_indices_for(dialect)
+                .map_err(Error::Dialect)?;
+            if indices.iter().enumerate().any(|(i, index)| i + 1 != *index) {
+                let indices: Vec<_> = indices.iter().map(usize::to_string).collect();
+                println!("group indices: {}", indices.join(", "));
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        ["test", pattern, url] => {
+            let pattern = parse(pattern)?;
+            let input =
+                parse_url(url, base_url.as_deref()).ok_or_else(|| Error::Url(url.to_string()))?;
+            let Some(result) = pattern.exec(&input) else {
+                println!("match: false");
+                return Ok(ExitCode::FAILURE);
+            };
+
+            println!("match: true");
+            let components = [
+                ("protocol", pattern.protocol(), &result.protocol),
+                ("username", pattern.username(), &result.username),
+                ("password", pattern.password(), &result.password),
+                ("hostname", pattern.hostname(), &result.hostname),
+                ("port", pattern.port(), &result.port),
+                ("pathname", pattern.pathname(), &result.pathname),
+                ("search", pattern.search(), &result.search),
+                ("hash", pattern.hash(), &result.hash),
+            ];
+            for (component, compiled, result) in components {
+                for name in compiled.group_names() {
+                    match &result.groups[name] {
+                        Some(value) => println!("  {component} {name} = {value:?}"),
+                        None => println!("  {component} {name} = (none)"),
+                    }
+                }
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        ["explain", pattern, url @ ..] if url.len() <= 1 => {
+            let pattern = parse(pattern)?;
+            let names = [
+                "protocol", "username", "password", "hostname", "port", "pathname", "search",
+                "hash",
+            ];
+            let components = [
+                pattern.protocol(),
+                pattern.username(),
+                pattern.password(),
+                pattern.hostname(),
+                pattern.port(),
+                pattern.pathname(),
+                pattern.search(),
+                pattern.hash(),
+            ];
+            // Components that match anything are left out.
+            for (name, component) in names.iter().zip(components) {
+                if component.pattern_string() == "*" {
+                    continue;
+                }
+                for (index, part) in component.parts().iter().enumerate() {
+                    println!("{name} {index}: {}", describe(part));
+                }
+            }
+
+            let [url] = url else {
+                return Ok(ExitCode::SUCCESS);
+            };
+            let input =
+                parse_url(url, base_url.as_deref()).ok_or_else(|| Error::Url(url.to_string()))?;
+            let explanation = pattern
+                .explain(&input)
+                .map_err(|_| Error::Url(url.to_string()))?;
+            let Some((component, explanation)) = explanation else {
+                println!("match: true");
+                return Ok(ExitCode::SUCCESS);
+            };
+
+            println!("match: false");
+            println!("matched: {:?}", explanation.matched);
+            match &explanation.part {
+                Some(part) => {
+                    println!(
+                        "rejected by {component} {}: {}",
+                        explanation.index,
+                        describe(part)
+                    );
+                    println!("expected: {}", explanation.expected);
+                }
+                None => println!("expected: end of {component}"),
+            }
+            println!("actual: {:?}", explanation.actual);
+            Ok(ExitCode::FAILURE)
+        }
+        [] => Err("missing command".into()),
+        [command, ..] => Err(format!("unknown command or wrong arguments for `{command}`").into()),
+    }
+}
+
+fn describe(part: &Part) -> String {
+    let (kind, name, value, constraint, modifier, prefix, suffix) = match part {
+        Part::FixedText { value, modifier } => {
+            let mut text = format!("fixed text {value:?}");
+            if let Some(modifier) = modifier {
+                text.push_str(&format!(", modifier `{modifier}`"));
+            }
+            return text;
+        }
+        Part::SegmentWildcard {
+            name,
+            modifier,
+            prefix,
+            suffix,
+        } => (
+            "segment wildcard",
+            name,
+            None,
+            None,
+            modifier,
+            prefix,
+            suffix,
+        ),
+        Part::FullWildcard {
+            name,
+            modifier,
+            prefix,
+            suffix,
+        } => ("full wildcard", name, None, None, modifier, prefix, suffix),
+        Part::RegExp {
+            name,
+            value,
+            modifier,
+            prefix,
+            suffix,
+            constraint,
+        } => (
+            "regexp",
+            name,
+            Some(value),
+            constraint.as_ref(),
+            modifier,
+            prefix,
+            suffix,
+        ),
+    };
+
+    let mut text = format!("{kind} `{name}`");
+    if let Some(value) = value {
+        text.push_str(&format!(" ({value})"));
+    }
+    if let Some(constraint) = constraint {
+        text.push_str(&format!(", constraint `{constraint}`"));
+    }
+    if let Some(modifier) = modifier {
+        text.push_str(&format!(", modifier `{modifier}`"));
+    }
+    if !prefix.is_empty() {
+        text.push_str(&format!(", prefix {prefix:?}"));
+    }
+    if !suffix.is_empty() {
+        text.push_str(&format!(", suffix {suffix:?}"));
+    }
+    text
+}
@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: MIT
+
+//! Conversion of WebExtension [match patterns](https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/Match_patterns)
+//! into URL pattern components.
+//!
+//! A match pattern ignores the userinfo, port and fragment of a URL, so those
+//! components become `*`. The path of a match pattern is matched against the
+//! path and query string together; a `*` extends into the query string only
+//! if it ends the path, because a pattern component can't span two URL
+//! components.
+
+use thiserror::Error;
+
+use crate::{escape_pattern_string, UrlPatternInit};
+
+/// The schemes `<all_urls>` matches.
+const ALL_URLS_PROTOCOL: &str = "(https?|wss?|ftp|data|file)";
+
+/// The schemes a match pattern can name, besides `*`.
+const SCHEMES: [&str; 6] = ["http", "https", "ws", "wss", "ftp", "file"];
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MatchPatternError {
+    #[error("missing `://` after the scheme")]
+    MissingSchemeSeparator,
+    #[error("unsupported scheme {0:?}")]
+    InvalidScheme(String),
+    #[error("missing host")]
+    MissingHost,
+    #[error("a `file` match pattern must not have a host")]
+    FileHost,
+    #[error("invalid host {0:?}, `*` is only allowed alone or as a leading `*.`")]
+    InvalidHost(String),
+    #[error("missing path after the host")]
+    MissingPath,
+}
+
+/// Escapes `input` as fixed text, except that every run of `*` becomes a full
+/// wildcard.
+fn wildcards(input: &str) -> String {
+    let mut result = String::new();
+    let mut wildcard = false;
+    for segment in input.split('*') {
+        if wildcard && !result.ends_with('*') {
+            result.push('*');
+        }
+        result.push_str(&escape_pattern_string(segment));
+        wildcard = true;
+    }
+    result
+}
+
+/// Converts a match pattern like `*://*.example.com/foo*` or `<all_urls>`
+/// into the components of an equivalent URL pattern.
+pub fn from_match_pattern(input: &str) -> Result<UrlPatternInit, MatchPatternError> {
+    let mut init = UrlPatternInit {
+        protocol: Some(ALL_URLS_PROTOCOL.into()),
+        username: Some("*".into()),
+        password: Some("*".into()),
+        hostname: Some("*".into()),
+        port: Some("*".into()),
+        pathname: Some("*".into()),
+        search: Some("*".into()),
+        hash: Some("*".into()),
+        base_url: None,
+    };
+    if input == "<all_urls>" {
+        return Ok(init);
+    }
+
+    let (scheme, rest) = input
+        .split_once("://")
+        .ok_or(MatchPatternError::MissingSchemeSeparator)?;
+    init.protocol = Some(match scheme {
+        // `*` only matches the web schemes.
+        "*" => "http{s}?".into(),
+        _ if SCHEMES.contains(&scheme) => scheme.into(),
+        _ => return Err(MatchPatternError::InvalidScheme(scheme.into())),
+    });
+
+    let (host, path) = rest
+        .find('/')
+        .map(|index| rest.split_at(index))
+        .ok_or(MatchPatternError::MissingPath)?;
+    init.hostname = Some(match host {
+        "" if scheme == "file" => String::new(),
+        "" => return Err(MatchPatternError::MissingHost),
+        _ if scheme == "file" => return Err(MatchPatternError::FileHost),
+        "*" => "*".into(),
+        _ => {
+            let (subdomains, domain) = match host.strip_prefix("*.") {
+                Some(domain) => ("{*.}?", domain),
+                None => ("", host),
+            };
+            if domain.is_empty()
+                || domain.contains('*')
+                || (domain.contains(':') && !domain.starts_with('['))
+            {
+                return Err(MatchPatternError::InvalidHost(host.into()));
+            }
+            format!("{subdomains}{}", escape_pattern_string(domain))
+        }
+    });
+
+    let (pathname, search) = match path.split_once('?') {
+        Some((pathname, search)) => (pathname, wildcards(search)),
+        None if path.ends_with('*') => (path, "*".into()),
+        None => (path, String::new()),
+    };
+    init.pathname = Some(wildcards(pathname));
+    init.search = Some(search);
+    Ok(init)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{UrlPattern, UrlPatternOptions};
+
+    fn pattern(input: &str) -> UrlPattern {
+        UrlPattern::from_init(
+            &from_match_pattern(input).unwrap(),
+            &UrlPatternOptions::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn match_patterns() {
+        let all_urls = pattern("<all_urls>");
+        assert!(all_urls.test_url("https://example.com/"));
+        assert!(all_urls.test_url("file:///etc/hosts"));
+        assert!(!all_urls.test_url("chrome://settings/"));
+
+        let subdomains = pattern("*://*.example.com/foo*");
+        assert!(subdomains.test_url("http://example.com/foo"));
+        assert!(subdomains.test_url("https://a.b.example.com:8080/foobar?x=1#top"));
+        assert!(!subdomains.test_url("https://notexample.com/foo"));
+        assert!(!subdomains.test_url("ws://example.com/foo"));
+        assert!(!subdomains.test_url("https://example.com/bar"));
+
+        let exact = pattern("https://example.com/a+b(c)");
+        assert!(exact.test_url("https://example.com/a+b(c)"));
+        assert!(!exact.test_url("https://example.com/a+b(c)?x"));
+
+        let file = pattern("file:///home/*");
+        assert!(file.test_url("file:///home/user/notes.txt"));
+
+        for (input, error) in [
+            ("https://example.com", MatchPatternError::MissingPath),
+            ("example.com/*", MatchPatternError::MissingSchemeSeparator),
+            (
+                "chrome://settings/*",
+                MatchPatternError::InvalidScheme("chrome".into()),
+            ),
+            ("https:///*", MatchPatternError::MissingHost),
+            ("file://host/*", MatchPatternError::FileHost),
+            (
+                "https://www.*.com/*",
+                MatchPatternError::InvalidHost("www.*.com".into()),
+            ),
+            (
+                "https://example.com:80/*",
+                MatchPatternError::InvalidHost("example.com:80".into()),
+            ),
+        ] {
+            assert_eq!(from_match_pattern(input), Err(error));
+        }
+    }
+}
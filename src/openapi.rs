@@ -0,0 +1,243 @@
+// SPDX-License-Identifier: MIT
+
+//! Conversion of pathname patterns into [OpenAPI path templates](https://spec.openapis.org/oas/v3.1.0#path-templating).
+
+use thiserror::Error;
+
+use crate::automaton::{search, Nfa};
+use crate::{AnalysisError, Modifier, Options, Part};
+
+/// An OpenAPI path template, like `/users/{id}`, and its path parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenApiPath {
+    pub template: String,
+    pub parameters: Vec<OpenApiParameter>,
+}
+
+/// A required path parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenApiParameter {
+    pub name: String,
+    pub schema: OpenApiSchema,
+}
+
+/// The schema of a path parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenApiSchema {
+    /// The `type` of the schema, always `string`.
+    pub schema_type: String,
+    /// The anchored `pattern` of the schema, for groups with a custom regexp.
+    pub pattern: Option<String>,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum OpenApiError {
+    #[error("optional `{0}` can't be expressed in an OpenAPI path")]
+    Optional(String),
+    #[error("repeated `{0}` can't be expressed in an OpenAPI path")]
+    Repeated(String),
+    #[error("full wildcard `{0}` can match more than one path segment")]
+    FullWildcard(String),
+    #[error("regexp group `{name}` can match more than one path segment, like {example:?}")]
+    RegExpDelimiter { name: String, example: String },
+    #[error("fixed text {0:?} contains a curly bracket")]
+    CurlyBracket(String),
+    #[error(transparent)]
+    Analysis(#[from] AnalysisError),
+}
+
+/// Returns an input with a `/` that the regexp group `value` matches.
+fn match_with_delimiter(name: &str, value: &str) -> Result<Option<String>, AnalysisError> {
+    let options = Options::pathname();
+    let group = Nfa::new(
+        &[Part::RegExp {
+            name: name.into(),
+            value: value.into(),
+            modifier: None,
+            prefix: String::new(),
+            suffix: String::new(),
+            constraint: None,
+        }],
+        &options,
+    )?;
+    let wildcard = || Part::FullWildcard {
+        name: String::new(),
+        modifier: None,
+        prefix: String::new(),
+        suffix: String::new(),
+    };
+    let delimiter = Part::FixedText {
+        value: "/".into(),
+        modifier: None,
+    };
+    let with_delimiter = Nfa::new(&[wildcard(), delimiter, wildcard()], &options)?;
+    Ok(search(&[&group, &with_delimiter], |accepting| {
+        accepting.iter().all(|&accepting| accepting)
+    }))
+}
+
+/// Converts the parts of a pathname pattern into an OpenAPI path template.
+///
+/// Segment wildcards become plain `string` parameters and regexp groups
+/// become parameters with a `pattern`. Parts with a modifier, full wildcards
+/// and regexp groups that can match a `/` have no equivalent and are
+/// rejected, as are regexp groups that can't be analyzed.
+pub fn openapi_path(parts: &[Part]) -> Result<OpenApiPath, OpenApiError> {
+    let mut path = OpenApiPath {
+        template: String::new(),
+        parameters: vec![],
+    };
+
+    let literal = |value: &str| {
+        if value.contains(['{', '}']) {
+            return Err(OpenApiError::CurlyBracket(value.into()));
+        }
+        Ok(value.to_string())
+    };
+
+    for part in parts {
+        let (name, modifier, pattern, prefix, suffix) = match part {
+            Part::FixedText { value, modifier } => {
+                match modifier {
+                    None => path.template.push_str(&literal(value)?),
+                    Some(Modifier::Optional) => return Err(OpenApiError::Optional(value.clone())),
+                    Some(_) => return Err(OpenApiError::Repeated(value.clone())),
+                }
+                continue;
+            }
+            Part::SegmentWildcard {
+                name,
+                modifier,
+                prefix,
+                suffix,
+            } => (name, modifier, None, prefix, suffix),
+            Part::FullWildcard { name, .. } => {
+                return Err(OpenApiError::FullWildcard(name.clone()));
+            }
+            Part::RegExp {
+                name,
+                value,
+                modifier,
+                prefix,
+                suffix,
+                ..
+            } => {
+                // A path parameter is a single segment.
+                if let Some(example) = match_with_delimiter(name, value)? {
+                    return Err(OpenApiError::RegExpDelimiter {
+                        name: name.clone(),
+                        example,
+                    });
+                }
+                (
+                    name,
+                    modifier,
+                    Some(format!("^(?:{value})$")),
+                    prefix,
+                    suffix,
+                )
+            }
+        };
+
+        match modifier {
+            None => {}
+            Some(Modifier::Optional) => return Err(OpenApiError::Optional(name.clone())),
+            Some(_) => return Err(OpenApiError::Repeated(name.clone())),
+        }
+
+        path.template.push_str(&literal(prefix)?);
+        path.template.push_str(&format!("{{{name}}}"));
+        path.template.push_str(&literal(suffix)?);
+
+        path.parameters.push(OpenApiParameter {
+            name: name.clone(),
+            schema: OpenApiSchema {
+                schema_type: "string".into(),
+                pattern,
+            },
+        });
+    }
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_pattern, Options};
+
+    fn convert(pattern: &str) -> Result<OpenApiPath, OpenApiError> {
+        openapi_path(&parse_pattern(pattern, &Options::pathname()).unwrap())
+    }
+
+    #[test]
+    fn path_templates() {
+        let path = convert(r"/users/:id(\d+)/files/:name.json").unwrap();
+        assert_eq!(path.template, "/users/{id}/files/{name}.json");
+        assert_eq!(
+            path.parameters,
+            [
+                OpenApiParameter {
+                    name: "id".into(),
+                    schema: OpenApiSchema {
+                        schema_type: "string".into(),
+                        pattern: Some(r"^(?:\d+)$".into()),
+                    },
+                },
+                OpenApiParameter {
+                    name: "name".into(),
+                    schema: OpenApiSchema {
+                        schema_type: "string".into(),
+                        pattern: None,
+                    },
+                },
+            ]
+        );
+
+        // The alternation stays anchored on both ends.
+        let path = convert("/:kind(user|group)").unwrap();
+        assert_eq!(
+            path.parameters[0].schema.pattern.as_deref(),
+            Some("^(?:user|group)$")
+        );
+
+        assert_eq!(
+            convert("/users/:id?"),
+            Err(OpenApiError::Optional("id".into()))
+        );
+        assert_eq!(
+            convert("/users/:id+"),
+            Err(OpenApiError::Repeated("id".into()))
+        );
+        assert_eq!(
+            convert("/files/*"),
+            Err(OpenApiError::FullWildcard("0".into()))
+        );
+        // `(.*)` is a full wildcard, other regexps are checked for `/`.
+        assert_eq!(
+            convert("/files/:rest(.*)"),
+            Err(OpenApiError::FullWildcard("rest".into()))
+        );
+        assert_eq!(
+            convert("/files/:rest(.+)"),
+            Err(OpenApiError::RegExpDelimiter {
+                name: "rest".into(),
+                example: "/".into(),
+            })
+        );
+        assert_eq!(
+            convert("/files/:path([a-z]+(?:/[a-z]+)*)"),
+            Err(OpenApiError::RegExpDelimiter {
+                name: "path".into(),
+                example: "a/a".into(),
+            })
+        );
+        assert!(convert("/files/:name([^/]+)").is_ok());
+        assert_eq!(
+            convert(r"/files/:name(\bx)"),
+            Err(OpenApiError::Analysis(AnalysisError::UnsupportedRegExp(
+                "name".into()
+            )))
+        );
+    }
+}
@@ -1,11 +1,15 @@
 // SPDX-License-Identifier: MIT
 
-use crate::tokenizer::Token;
-use crate::{Options, ParseError};
-use std::fmt;
-
-#[derive(Debug)]
-pub(crate) enum Modifier {
+use crate::tokenizer::{Token, TokenKind};
+use crate::{full_wildcard_regexp, Options, ParseError, RegexDialect};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// <https://urlpattern.spec.whatwg.org/#part-modifier>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
     Optional,
     ZeroOrMore,
     OneOrMore,
@@ -25,9 +29,9 @@ impl fmt::Display for Modifier {
     }
 }
 
-// https://urlpattern.spec.whatwg.org/#part
-#[derive(Debug)]
-pub(crate) enum Part {
+/// <https://urlpattern.spec.whatwg.org/#part>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Part {
     FixedText {
         value: String,
         modifier: Option<Modifier>,
@@ -38,6 +42,10 @@ pub(crate) enum Part {
         modifier: Option<Modifier>,
         prefix: String,
         suffix: String,
+        /// The name of the constraint the regexp came from, like `int` for
+        /// `:id<int>`. Only such groups run the constraint's validation
+        /// function.
+        constraint: Option<String>,
     },
     SegmentWildcard {
         name: String,
@@ -54,20 +62,22 @@ pub(crate) enum Part {
 }
 
 pub(crate) struct Parser<'a> {
-    tokens: &'a [Token],
+    tokens: &'a [Token<'a>],
     options: &'a Options,
     index: usize,
     pending_fixed_value: String,
+    next_numeric_name: usize,
     pub(crate) parts: Vec<Part>,
 }
 
-impl<'a> Parser<'_> {
-    pub(crate) fn new(tokens: &'a [Token], options: &'a Options) -> Parser<'a> {
+impl<'a> Parser<'a> {
+    pub(crate) fn new(tokens: &'a [Token<'a>], options: &'a Options) -> Parser<'a> {
         Parser {
             tokens,
             options,
             index: 0,
             pending_fixed_value: String::new(),
+            next_numeric_name: 0,
             parts: vec![],
         }
     }
@@ -76,35 +86,39 @@ impl<'a> Parser<'_> {
     pub(crate) fn parse(&mut self) -> Result<(), ParseError> {
         loop {
             // 1. Let char token be the result of running try to consume a token given parser and "char".
-            let char_token = self.try_consume_token(|token| matches!(token, Token::Char(_)));
+            let char_token = self.try_consume_token(|token| matches!(token, TokenKind::Char(_)));
 
             // 2. Let name token be the result of running try to consume a token given parser and "name".
-            let name_token = self.try_consume_token(|token| matches!(token, Token::Name(_)));
+            let name_token = self.try_consume_token(|token| matches!(token, TokenKind::Name(_)));
 
             // 3. Let regexp or wildcard token be the result of running try to consume a regexp or wildcard token given parser and name token.
-            let regexp_or_wildcard = self.try_consume_regexp_or_wildcard(name_token.as_ref());
+            let (regexp_or_wildcard, constraint) = match self.try_consume_constraint()? {
+                Some((regexp, constraint)) => (Some(regexp), Some(constraint)),
+                None => (
+                    self.try_consume_regexp_or_wildcard(name_token.as_ref()),
+                    None,
+                ),
+            };
 
             // 4. If name token is not null or regexp or wildcard token is not null:
             if name_token.is_some() || regexp_or_wildcard.is_some() {
                 // 1. Let prefix be the empty string.
                 // 2. If char token is not null then set prefix to char token’s value.
                 let mut prefix: String = match char_token {
-                    Some(Token::Char(chr)) => chr.into(),
+                    Some(TokenKind::Char(chr)) => chr.into(),
                     Some(_) => panic!("unexpected token {:?}", char_token),
                     None => "".into(),
                 };
 
                 // 3. If prefix is not the empty string and not options’s prefix code point:
-                if !prefix.is_empty() {
-                    if let Some(opt_prefix) = self.options.prefix {
-                        if prefix != opt_prefix.to_string() {
-                            // 1. Append prefix to the end of parser’s pending fixed value.
-                            self.pending_fixed_value.push_str(&prefix);
-
-                            // 2. Set prefix to the empty string.
-                            prefix.clear()
-                        }
-                    }
+                if !prefix.is_empty()
+                    && self.options.prefix.map(String::from) != Some(prefix.clone())
+                {
+                    // 1. Append prefix to the end of parser’s pending fixed value.
+                    self.pending_fixed_value.push_str(&prefix);
+
+                    // 2. Set prefix to the empty string.
+                    prefix.clear()
                 }
 
                 // Run maybe add a part from the pending fixed value given parser.
@@ -114,7 +128,14 @@ impl<'a> Parser<'_> {
                 let modifier = self.try_consume_modifier();
 
                 // Run add a part given parser, prefix, name token, regexp or wildcard token, the empty string, and modifier token.
-                self.add_part(prefix, name_token, regexp_or_wildcard, "".into(), modifier);
+                self.add_part(
+                    prefix,
+                    name_token,
+                    regexp_or_wildcard,
+                    constraint,
+                    "".into(),
+                    modifier,
+                )?;
 
                 // Continue
                 continue;
@@ -122,16 +143,18 @@ impl<'a> Parser<'_> {
 
             // Let fixed token be char token.
             // If fixed token is null, then set fixed token to the result of running try to consume a token given parser and "escaped-char".
-            let fixed_token = char_token
-                .or_else(|| self.try_consume_token(|token| matches!(token, Token::EscapedChar(_))));
+            let fixed_token = char_token.or_else(|| {
+                self.try_consume_token(|token| matches!(token, TokenKind::EscapedChar(_)))
+            });
 
             // If fixed token is not null:
             if let Some(fixed_token) = fixed_token {
-                let value = if let Token::Char(value) | Token::EscapedChar(value) = fixed_token {
-                    value
-                } else {
-                    panic!("impossible");
-                };
+                let value =
+                    if let TokenKind::Char(value) | TokenKind::EscapedChar(value) = fixed_token {
+                        value
+                    } else {
+                        panic!("impossible");
+                    };
 
                 // Append fixed token’s value to parser’s pending fixed value.
                 self.pending_fixed_value.push(value);
@@ -142,21 +165,30 @@ impl<'a> Parser<'_> {
 
             // 8. Let open token be the result of running try to consume a token given parser and "open".
             // 9. If open token is not null:
-            if let Some(_) = self.try_consume_token(|token| matches!(token, Token::Open)) {
+            if self
+                .try_consume_token(|token| matches!(token, TokenKind::Open))
+                .is_some()
+            {
                 // Set prefix be the result of running consume text given parser.
                 let prefix = self.consume_text();
 
                 // Set name token to the result of running try to consume a token given parser and "name".
-                let name = self.try_consume_token(|token| matches!(token, Token::Name(_)));
+                let name = self.try_consume_token(|token| matches!(token, TokenKind::Name(_)));
 
                 // Set regexp or wildcard token to the result of running try to consume a regexp or wildcard token given parser and name token.
-                let regexp_or_wildcard = self.try_consume_regexp_or_wildcard(name.as_ref());
+                let (regexp_or_wildcard, constraint) = match self.try_consume_constraint()? {
+                    Some((regexp, constraint)) => (Some(regexp), Some(constraint)),
+                    None => (self.try_consume_regexp_or_wildcard(name.as_ref()), None),
+                };
 
                 // Let suffix be the result of running consume text given parser.
                 let suffix = self.consume_text();
 
                 // Run consume a required token given parser and "close".
-                if let None = self.try_consume_token(|token| matches!(token, Token::Close)) {
+                if self
+                    .try_consume_token(|token| matches!(token, TokenKind::Close))
+                    .is_none()
+                {
                     return Err(ParseError::MissingClosingCurly);
                 }
 
@@ -164,7 +196,14 @@ impl<'a> Parser<'_> {
                 let modifier = self.try_consume_modifier();
 
                 // Run add a part given parser, prefix, name token, regexp or wildcard token, suffix, and modifier token.
-                self.add_part(prefix, name, regexp_or_wildcard, suffix, modifier);
+                self.add_part(
+                    prefix,
+                    name,
+                    regexp_or_wildcard,
+                    constraint,
+                    suffix,
+                    modifier,
+                )?;
 
                 // Continue.
                 continue;
@@ -174,7 +213,10 @@ impl<'a> Parser<'_> {
             self.maybe_add_part_from_pending_fixed_value();
 
             // Run consume a required token given parser and "end".
-            if let None = self.try_consume_token(|token| matches!(token, Token::End)) {
+            if self
+                .try_consume_token(|token| matches!(token, TokenKind::End))
+                .is_none()
+            {
                 return Err(ParseError::UnexpectedEnd);
             }
 
@@ -184,11 +226,28 @@ impl<'a> Parser<'_> {
         Ok(())
     }
 
+    /// The byte offset in the input at which `error`, returned by
+    /// [`Parser::parse`], was found.
+    #[cfg_attr(not(feature = "regex"), allow(dead_code))]
+    pub(crate) fn error_position(&self, error: &ParseError) -> usize {
+        let token = match error {
+            // The constraint token was already consumed.
+            ParseError::UnknownConstraint(_) => &self.tokens[self.index - 1],
+            ParseError::DuplicateName(name) => self.tokens[..self.index]
+                .iter()
+                .rev()
+                .find(|token| matches!(token.kind, TokenKind::Name(other) if other == name))
+                .unwrap_or(&self.tokens[self.index]),
+            _ => &self.tokens[self.index],
+        };
+        token.span.start
+    }
+
     fn consume_text(&mut self) -> String {
         let mut result = String::new();
         loop {
-            match self.tokens[self.index] {
-                Token::Char(chr) | Token::EscapedChar(chr) => {
+            match self.tokens[self.index].kind {
+                TokenKind::Char(chr) | TokenKind::EscapedChar(chr) => {
                     result.push(chr);
                     self.index += 1;
                 }
@@ -197,20 +256,20 @@ impl<'a> Parser<'_> {
         }
     }
 
-    fn try_consume_token(&mut self, matches: fn(&Token) -> bool) -> Option<Token> {
-        let next_token = &self.tokens[self.index];
-        if !matches(next_token) {
+    fn try_consume_token(&mut self, matches: fn(&TokenKind) -> bool) -> Option<TokenKind<'a>> {
+        let next_token = self.tokens[self.index].kind;
+        if !matches(&next_token) {
             return None;
         }
         self.index += 1;
-        Some(next_token.clone())
+        Some(next_token)
     }
 
     fn try_consume_modifier(&mut self) -> Option<Modifier> {
-        let modifier = match self.tokens[self.index] {
-            Token::QuestionMark => Modifier::Optional,
-            Token::Plus => Modifier::OneOrMore,
-            Token::Asterisk => Modifier::ZeroOrMore,
+        let modifier = match self.tokens[self.index].kind {
+            TokenKind::QuestionMark => Modifier::Optional,
+            TokenKind::Plus => Modifier::OneOrMore,
+            TokenKind::Asterisk => Modifier::ZeroOrMore,
             _ => return None,
         };
 
@@ -218,14 +277,33 @@ impl<'a> Parser<'_> {
         Some(modifier)
     }
 
+    /// Consumes a constraint token and returns a regexp token with the
+    /// fragment of the constraint instead, and the name of the constraint.
+    fn try_consume_constraint(&mut self) -> Result<Option<(TokenKind<'a>, &'a str)>, ParseError> {
+        let Some(TokenKind::Constraint(name)) =
+            self.try_consume_token(|token| matches!(token, TokenKind::Constraint(_)))
+        else {
+            return Ok(None);
+        };
+        let options = self.options;
+        let constraint = options
+            .constraints
+            .get(name)
+            .ok_or_else(|| ParseError::UnknownConstraint(name.into()))?;
+        Ok(Some((TokenKind::RegExp(&constraint.regexp), name)))
+    }
+
     /// <https://urlpattern.spec.whatwg.org/#try-to-consume-a-regexp-or-wildcard-token>
-    fn try_consume_regexp_or_wildcard(&mut self, name_token: Option<&Token>) -> Option<Token> {
+    fn try_consume_regexp_or_wildcard(
+        &mut self,
+        name_token: Option<&TokenKind>,
+    ) -> Option<TokenKind<'a>> {
         // 1. Let token be the result of running try to consume a token given parser and "regexp".
-        let token = self.try_consume_token(|token| matches!(token, Token::RegExp(_)));
+        let token = self.try_consume_token(|token| matches!(token, TokenKind::RegExp(_)));
 
         // 2. If name token is null and token is null, then set token to the result of running try to consume a token given parser and "asterisk".
         if name_token.is_none() && token.is_none() {
-            return self.try_consume_token(|token| matches!(token, Token::Asterisk));
+            return self.try_consume_token(|token| matches!(token, TokenKind::Asterisk));
         }
 
         // 3. Return token.
@@ -236,11 +314,12 @@ impl<'a> Parser<'_> {
     fn add_part(
         &mut self,
         prefix: String,
-        name: Option<Token>,
-        regexp_or_wildcard: Option<Token>,
+        name: Option<TokenKind>,
+        regexp_or_wildcard: Option<TokenKind>,
+        constraint: Option<&str>,
         suffix: String,
         modifier: Option<Modifier>,
-    ) {
+    ) -> Result<(), ParseError> {
         // 1. Let modifier be "none".
         // 2. If modifier token is not null:
         // ...
@@ -255,7 +334,7 @@ impl<'a> Parser<'_> {
             self.pending_fixed_value.push_str(&prefix);
 
             // Return
-            return;
+            return Ok(());
         }
 
         // 4. Run maybe add a part from the pending fixed value given parser.
@@ -268,7 +347,7 @@ impl<'a> Parser<'_> {
 
             // If prefix is the empty string, then return.
             if prefix.is_empty() {
-                return;
+                return Ok(());
             }
 
             // Let encoded value be the result of running parser’s encoding callback given prefix.
@@ -283,17 +362,20 @@ impl<'a> Parser<'_> {
             });
 
             // Return.
-            return;
+            return Ok(());
         }
 
+        let segment_wildcard_regexp =
+            RegexDialect::EcmaScript.segment_wildcard(self.options.delimiter);
+
         // 6. Let regexp value be the empty string.
         let regexp_value = match regexp_or_wildcard {
             // 7. If regexp or wildcard token is null, then set regexp value to parser’s segment wildcard regexp.
-            None => "<segment wildcard>".into(),
+            None => segment_wildcard_regexp.clone(),
             // 8. Otherwise if regexp or wildcard token’s type is "asterisk", then set regexp value to the full wildcard regexp value.
-            Some(Token::Asterisk) => "<full wildcard>".into(),
+            Some(TokenKind::Asterisk) => full_wildcard_regexp().into(),
             // 9. Otherwise set regexp value to regexp or wildcard token’s value.
-            Some(Token::RegExp(ref value)) => value.clone(),
+            Some(TokenKind::RegExp(value)) => value.to_string(),
             Some(_) => panic!("invalid regexp_or_wildcard token"),
         };
 
@@ -306,31 +388,47 @@ impl<'a> Parser<'_> {
         // 14. If name token is not null, then set name to name token’s value.
         let name = if name.is_some() {
             match name {
-                Some(Token::Name(name)) => name.clone(),
+                Some(TokenKind::Name(name)) => name.to_string(),
                 _ => panic!("invalid name token"),
             }
         } else {
             // 15. Otherwise if regexp or wildcard token is not null:
             // XXX: Spec bug must be non-null.
             assert!(regexp_or_wildcard.is_some());
-            "1".into()
+
+            // 1. Set name to parser’s next numeric name, serialized.
+            // 2. Increment parser’s next numeric name by 1.
+            self.next_numeric_name += 1;
+            (self.next_numeric_name - 1).to_string()
         };
 
         // 16. If the result of running is a duplicate name given parser and name is true, then throw a TypeError.
-        // TODO:
+        if self.is_duplicate_name(&name) {
+            return Err(ParseError::DuplicateName(name));
+        }
 
         // 17. Let encoded prefix be the result of running parser’s encoding callback given prefix.
         // 18. Let encoded suffix be the result of running parser’s encoding callback given suffix.
 
         // Let part be a new part whose type is type, value is regexp value, modifier is modifier, name is name, prefix is encoded prefix, and suffix is encoded suffix.
-        if regexp_value == "<segment wildcard>" {
+        // A constraint stays a regexp part even if its fragment is a wildcard.
+        if constraint.is_some() {
+            self.parts.push(Part::RegExp {
+                name,
+                value: regexp_value,
+                modifier,
+                prefix,
+                suffix,
+                constraint: constraint.map(String::from),
+            })
+        } else if regexp_value == segment_wildcard_regexp {
             self.parts.push(Part::SegmentWildcard {
                 name,
                 modifier,
                 prefix,
                 suffix,
             })
-        } else if regexp_value == "<full wildcard>" {
+        } else if regexp_value == full_wildcard_regexp() {
             self.parts.push(Part::FullWildcard {
                 name,
                 modifier,
@@ -344,8 +442,27 @@ impl<'a> Parser<'_> {
                 modifier,
                 prefix,
                 suffix,
+                constraint: None,
             })
         }
+
+        Ok(())
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#is-a-duplicate-name>
+    fn is_duplicate_name(&self, name: &str) -> bool {
+        self.parts.iter().any(|part| match part {
+            Part::FixedText { .. } => false,
+            Part::RegExp {
+                name: part_name, ..
+            }
+            | Part::SegmentWildcard {
+                name: part_name, ..
+            }
+            | Part::FullWildcard {
+                name: part_name, ..
+            } => part_name == name,
+        })
     }
 
     fn maybe_add_part_from_pending_fixed_value(&mut self) {
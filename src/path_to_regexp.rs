@@ -0,0 +1,563 @@
+// SPDX-License-Identifier: MIT
+
+//! Conversion of [path-to-regexp](https://github.com/pillarjs/path-to-regexp)
+//! route strings, as used by Express and friends, into URL pattern parts.
+//!
+//! The route options of path-to-regexp are not part of the route string and
+//! are not carried over: by default it matches case-insensitively
+//! (`sensitive: false`) and allows a trailing slash (`strict: false`). Routes
+//! for which that makes a difference get an [`ImportWarning`].
+
+use std::collections::HashSet;
+
+use thiserror::Error;
+
+use crate::{generate_pattern_string, Modifier, Options, Part};
+
+/// The major version of path-to-regexp whose syntax a route string uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathToRegexpVersion {
+    /// Also covers the 0.1.x releases used by Express 4.
+    V1,
+    V2,
+    V3,
+    V4,
+    V5,
+    V6,
+}
+
+impl PathToRegexpVersion {
+    /// A bare `*` is an unnamed `(.*)` parameter, instead of literal text.
+    fn has_asterisk(self) -> bool {
+        self == PathToRegexpVersion::V1
+    }
+
+    /// `{` and `}` group a parameter with a custom prefix and suffix.
+    fn has_groups(self) -> bool {
+        self == PathToRegexpVersion::V6
+    }
+
+    /// The default parameter pattern excludes the prefix of the parameter,
+    /// instead of always excluding `/`.
+    fn has_prefix_delimiters(self) -> bool {
+        self != PathToRegexpVersion::V6
+    }
+}
+
+/// A difference between path-to-regexp and URL pattern semantics that was
+/// found while converting a route.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportWarning {
+    /// The parameter follows a `.`, so path-to-regexp stopped it at the next
+    /// `.`, but the imported segment wildcard only stops at the next `/`.
+    DotDelimitedParameter { name: String },
+    /// The parameter name starts with a digit and can't be written in a
+    /// pattern string, or be told apart from unnamed groups, so it was
+    /// renamed by prefixing it with `_`.
+    NumericName { name: String, renamed: String },
+    /// The route has fixed text with letters, which path-to-regexp matches
+    /// case-insensitively by default. Compile the parts with
+    /// [`Options::ignore_case`] to keep that.
+    CaseInsensitive,
+    /// The route doesn't end with `/`, and path-to-regexp by default also
+    /// matches it with a trailing `/` appended.
+    TrailingSlash,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ImportError {
+    #[error("unexpected `{0}` at index {1}")]
+    Unexpected(char, usize),
+    #[error("unexpected end of route")]
+    UnexpectedEnd,
+    #[error("missing parameter name at index {0}")]
+    MissingName(usize),
+    #[error("unbalanced parentheses in pattern at index {0}")]
+    UnbalancedPattern(usize),
+    #[error("capturing groups are not allowed in the pattern at index {0}")]
+    CapturingGroup(usize),
+    #[error("duplicate parameter name `{0}`")]
+    DuplicateName(String),
+}
+
+/// The result of converting a path-to-regexp route string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedRoute {
+    pub parts: Vec<Part>,
+    pub warnings: Vec<ImportWarning>,
+}
+
+impl ImportedRoute {
+    /// Returns the pathname pattern string for the imported parts.
+    pub fn pattern_string(&self) -> String {
+        generate_pattern_string(&self.parts, &Options::pathname())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    Pattern(String),
+    Name(String),
+    Char(char),
+    EscapedChar(char),
+    Modifier(Modifier),
+    Asterisk,
+    End,
+}
+
+/// Splits a route into tokens, each with its byte offset in the route.
+fn lex(route: &str, version: PathToRegexpVersion) -> Result<Vec<(Token, usize)>, ImportError> {
+    let mut tokens = vec![];
+
+    let mut iter = route.char_indices().peekable();
+    while let Some((index, chr)) = iter.next() {
+        let token = match chr {
+            '*' | '+' | '?' => {
+                let modifier = match chr {
+                    '*' => Modifier::ZeroOrMore,
+                    '+' => Modifier::OneOrMore,
+                    _ => Modifier::Optional,
+                };
+
+                if matches!(
+                    tokens.last(),
+                    Some((Token::Name(_) | Token::Pattern(_) | Token::Close, _))
+                ) {
+                    Token::Modifier(modifier)
+                } else if chr == '*' && version.has_asterisk() {
+                    Token::Asterisk
+                } else if version.has_groups() {
+                    // v6 rejects this when parsing.
+                    Token::Modifier(modifier)
+                } else {
+                    // Before v6 a modifier that doesn't follow a parameter is literal text.
+                    Token::Char(chr)
+                }
+            }
+            '\\' => match iter.next() {
+                Some((_, escaped)) => Token::EscapedChar(escaped),
+                None => return Err(ImportError::UnexpectedEnd),
+            },
+            '{' if version.has_groups() => Token::Open,
+            '}' if version.has_groups() => Token::Close,
+            ':' => {
+                let mut name = String::new();
+                while let Some(&(_, chr)) = iter.peek() {
+                    if !(chr.is_ascii_alphanumeric() || chr == '_') {
+                        break;
+                    }
+                    name.push(chr);
+                    iter.next();
+                }
+
+                if name.is_empty() {
+                    // Before v6 a `:` without a name is literal text.
+                    if version.has_groups() {
+                        return Err(ImportError::MissingName(index));
+                    }
+                    Token::Char(':')
+                } else {
+                    Token::Name(name)
+                }
+            }
+            '(' => {
+                let mut depth = 1;
+                let mut pattern = String::new();
+                while let Some((_, chr)) = iter.next() {
+                    match chr {
+                        '\\' => {
+                            pattern.push(chr);
+                            match iter.next() {
+                                Some((_, escaped)) => pattern.push(escaped),
+                                None => return Err(ImportError::UnbalancedPattern(index)),
+                            }
+                            continue;
+                        }
+                        ')' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        '(' => {
+                            if iter.peek().map(|&(_, chr)| chr) != Some('?') {
+                                return Err(ImportError::CapturingGroup(index));
+                            }
+                            depth += 1;
+                        }
+                        _ => {}
+                    }
+                    pattern.push(chr);
+                }
+
+                if depth != 0 || pattern.is_empty() {
+                    return Err(ImportError::UnbalancedPattern(index));
+                }
+                Token::Pattern(pattern)
+            }
+            _ => Token::Char(chr),
+        };
+        tokens.push((token, index));
+    }
+
+    tokens.push((Token::End, route.len()));
+    Ok(tokens)
+}
+
+struct Importer {
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+    version: PathToRegexpVersion,
+    next_numeric_name: usize,
+    pending_fixed_value: String,
+    names: HashSet<String>,
+    route: ImportedRoute,
+}
+
+impl Importer {
+    fn try_consume(&mut self, matches: fn(&Token) -> bool) -> Option<Token> {
+        let (token, _) = &self.tokens[self.index];
+        if !matches(token) {
+            return None;
+        }
+        self.index += 1;
+        Some(token.clone())
+    }
+
+    fn try_consume_modifier(&mut self) -> Option<Modifier> {
+        match self.try_consume(|token| matches!(token, Token::Modifier(_))) {
+            Some(Token::Modifier(modifier)) => Some(modifier),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the route has a parameter called `name`.
+    fn has_name_token(&self, name: &str) -> bool {
+        self.tokens
+            .iter()
+            .any(|(token, _)| matches!(token, Token::Name(other) if other == name))
+    }
+
+    fn consume_text(&mut self) -> String {
+        let mut result = String::new();
+        while let (Token::Char(chr) | Token::EscapedChar(chr), _) = self.tokens[self.index] {
+            result.push(chr);
+            self.index += 1;
+        }
+        result
+    }
+
+    fn flush_pending_fixed_value(&mut self) {
+        if self.pending_fixed_value.is_empty() {
+            return;
+        }
+
+        self.route.parts.push(Part::FixedText {
+            value: std::mem::take(&mut self.pending_fixed_value),
+            modifier: None,
+        });
+    }
+
+    fn add_parameter(
+        &mut self,
+        name: Option<String>,
+        pattern: Option<String>,
+        prefix: String,
+        suffix: String,
+        modifier: Option<Modifier>,
+    ) -> Result<(), ImportError> {
+        if name.is_none() && pattern.is_none() {
+            let value = prefix + &suffix;
+            if modifier.is_none() {
+                self.pending_fixed_value.push_str(&value);
+            } else if !value.is_empty() {
+                self.flush_pending_fixed_value();
+                self.route.parts.push(Part::FixedText { value, modifier });
+            }
+            return Ok(());
+        }
+
+        self.flush_pending_fixed_value();
+
+        let name = match name {
+            Some(name) if name.starts_with(|chr: char| chr.is_ascii_digit()) => {
+                let mut renamed = format!("_{name}");
+                while self.names.contains(&renamed) || self.has_name_token(&renamed) {
+                    renamed.insert(0, '_');
+                }
+                self.route.warnings.push(ImportWarning::NumericName {
+                    name,
+                    renamed: renamed.clone(),
+                });
+                renamed
+            }
+            Some(name) => name,
+            None => {
+                self.next_numeric_name += 1;
+                (self.next_numeric_name - 1).to_string()
+            }
+        };
+
+        if !self.names.insert(name.clone()) {
+            return Err(ImportError::DuplicateName(name));
+        }
+
+        let part = match pattern.as_deref() {
+            None | Some("[^\\/]+?" | "[^/]+?") => {
+                if self.version.has_prefix_delimiters() && prefix == "." && pattern.is_none() {
+                    self.route
+                        .warnings
+                        .push(ImportWarning::DotDelimitedParameter { name: name.clone() });
+                }
+                Part::SegmentWildcard {
+                    name,
+                    modifier,
+                    prefix,
+                    suffix,
+                }
+            }
+            Some(".*") => Part::FullWildcard {
+                name,
+                modifier,
+                prefix,
+                suffix,
+            },
+            Some(value) => Part::RegExp {
+                name,
+                value: value.into(),
+                modifier,
+                prefix,
+                suffix,
+                constraint: None,
+            },
+        };
+        self.route.parts.push(part);
+        Ok(())
+    }
+
+    fn import(mut self) -> Result<ImportedRoute, ImportError> {
+        loop {
+            let char_token = self.try_consume(|token| matches!(token, Token::Char(_)));
+            let name = self.try_consume(|token| matches!(token, Token::Name(_)));
+            let pattern = self.try_consume(|token| matches!(token, Token::Pattern(_)));
+            let asterisk = if name.is_none() && pattern.is_none() {
+                self.try_consume(|token| matches!(token, Token::Asterisk))
+            } else {
+                None
+            };
+
+            if name.is_some() || pattern.is_some() || asterisk.is_some() {
+                let mut prefix = match char_token {
+                    Some(Token::Char(chr)) => chr.to_string(),
+                    _ => String::new(),
+                };
+
+                // Only `/` and `.` are prefixes, everything else is literal text.
+                if prefix != "/" && prefix != "." {
+                    self.pending_fixed_value.push_str(&prefix);
+                    prefix.clear();
+                }
+
+                // The v1 asterisk can't have a modifier.
+                let modifier = if asterisk.is_some() {
+                    None
+                } else {
+                    self.try_consume_modifier()
+                };
+
+                let name = match name {
+                    Some(Token::Name(name)) => Some(name),
+                    _ => None,
+                };
+                let pattern = match (pattern, asterisk) {
+                    (Some(Token::Pattern(pattern)), _) => Some(pattern),
+                    (_, Some(_)) => Some(".*".into()),
+                    _ => None,
+                };
+
+                self.add_parameter(name, pattern, prefix, String::new(), modifier)?;
+                continue;
+            }
+
+            let fixed_token = char_token
+                .or_else(|| self.try_consume(|token| matches!(token, Token::EscapedChar(_))));
+            if let Some(Token::Char(chr) | Token::EscapedChar(chr)) = fixed_token {
+                self.pending_fixed_value.push(chr);
+                continue;
+            }
+
+            if self
+                .try_consume(|token| matches!(token, Token::Open))
+                .is_some()
+            {
+                let prefix = self.consume_text();
+                let name = match self.try_consume(|token| matches!(token, Token::Name(_))) {
+                    Some(Token::Name(name)) => Some(name),
+                    _ => None,
+                };
+                let pattern = match self.try_consume(|token| matches!(token, Token::Pattern(_))) {
+                    Some(Token::Pattern(pattern)) => Some(pattern),
+                    _ => None,
+                };
+                let suffix = self.consume_text();
+
+                if self
+                    .try_consume(|token| matches!(token, Token::Close))
+                    .is_none()
+                {
+                    return Err(self.unexpected());
+                }
+
+                let modifier = self.try_consume_modifier();
+                self.add_parameter(name, pattern, prefix, suffix, modifier)?;
+                continue;
+            }
+
+            self.flush_pending_fixed_value();
+
+            if self
+                .try_consume(|token| matches!(token, Token::End))
+                .is_none()
+            {
+                return Err(self.unexpected());
+            }
+
+            self.add_option_warnings();
+            return Ok(self.route);
+        }
+    }
+
+    /// Warns about the differences caused by the default route options of
+    /// path-to-regexp.
+    fn add_option_warnings(&mut self) {
+        let has_case = |text: &str| {
+            text.chars()
+                .any(|chr| chr.is_lowercase() || chr.is_uppercase())
+        };
+        let case_sensitive = self.route.parts.iter().any(|part| match part {
+            Part::FixedText { value, .. } => has_case(value),
+            Part::RegExp { prefix, suffix, .. }
+            | Part::SegmentWildcard { prefix, suffix, .. }
+            | Part::FullWildcard { prefix, suffix, .. } => has_case(prefix) || has_case(suffix),
+        });
+        if case_sensitive {
+            self.route.warnings.push(ImportWarning::CaseInsensitive);
+        }
+
+        let trailing_slash = matches!(
+            self.route.parts.last(),
+            Some(Part::FixedText { value, modifier: None }) if value.ends_with('/')
+        );
+        if !trailing_slash {
+            self.route.warnings.push(ImportWarning::TrailingSlash);
+        }
+    }
+
+    fn unexpected(&self) -> ImportError {
+        let (token, offset) = &self.tokens[self.index];
+        let chr = match token {
+            Token::Open => '{',
+            Token::Close => '}',
+            Token::Pattern(_) => '(',
+            Token::Name(_) => ':',
+            Token::Char(chr) | Token::EscapedChar(chr) => *chr,
+            Token::Modifier(modifier) => modifier.to_string().chars().next().unwrap(),
+            Token::Asterisk => '*',
+            Token::End => return ImportError::UnexpectedEnd,
+        };
+        ImportError::Unexpected(chr, *offset)
+    }
+}
+
+/// Converts a path-to-regexp route string into the parts of an equivalent
+/// pathname pattern.
+pub fn import_path_to_regexp(
+    route: &str,
+    version: PathToRegexpVersion,
+) -> Result<ImportedRoute, ImportError> {
+    let importer = Importer {
+        tokens: lex(route, version)?,
+        index: 0,
+        version,
+        next_numeric_name: 0,
+        pending_fixed_value: String::new(),
+        names: HashSet::new(),
+        route: ImportedRoute {
+            parts: vec![],
+            warnings: vec![],
+        },
+    };
+    importer.import()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Component;
+
+    fn pattern_string(route: &str, version: PathToRegexpVersion) -> String {
+        let route = import_path_to_regexp(route, version).unwrap();
+        let pattern_string = route.pattern_string();
+
+        // The pattern string has to parse back into the same parts.
+        let component = Component::compile(&pattern_string, &Options::pathname()).unwrap();
+        assert_eq!(component.parts(), route.parts);
+
+        pattern_string
+    }
+
+    #[test]
+    fn express_routes() {
+        use PathToRegexpVersion::*;
+
+        assert_eq!(pattern_string(r"/user/:id(\d+)?", V1), r"/user/:id(\d+)?");
+        assert_eq!(pattern_string("/files/*", V1), "/files/*");
+        assert_eq!(pattern_string("/files/*", V5), r"/files/\*");
+        assert_eq!(pattern_string("/:path*", V6), "/:path*");
+        assert_eq!(pattern_string("/:a-:b", V3), "/:a-:b");
+        assert_eq!(pattern_string(r"/(\d+)/(.*)", V2), r"/(\d+)/*");
+        assert_eq!(pattern_string("/books{/:id}?", V6), "/books/:id?");
+        assert_eq!(pattern_string("/books{-:id-}?", V6), "/books{-:id-}?");
+        assert_eq!(pattern_string(r"/a\:b", V6), r"/a\:b");
+
+        let route = import_path_to_regexp("/file.:ext", V1).unwrap();
+        assert_eq!(route.pattern_string(), "/file{.:ext}");
+        assert_eq!(
+            route.warnings,
+            [
+                ImportWarning::DotDelimitedParameter { name: "ext".into() },
+                ImportWarning::CaseInsensitive,
+                ImportWarning::TrailingSlash,
+            ]
+        );
+        // Numeric names don't clash with unnamed groups or other names.
+        assert_eq!(pattern_string("/:0/(.*)", V1), "/:_0/*");
+        let route = import_path_to_regexp("/:0/:_0/(.*)/", V1).unwrap();
+        assert_eq!(pattern_string("/:0/:_0/(.*)/", V1), "/:__0/:_0/*/");
+        assert_eq!(
+            route.warnings,
+            [ImportWarning::NumericName {
+                name: "0".into(),
+                renamed: "__0".into(),
+            }]
+        );
+
+        let route = import_path_to_regexp("/:id/", V6).unwrap();
+        assert_eq!(route.warnings, []);
+
+        assert_eq!(
+            import_path_to_regexp("/:a/:a", V6),
+            Err(ImportError::DuplicateName("a".into()))
+        );
+        assert_eq!(
+            import_path_to_regexp("/a(b(c))", V6),
+            Err(ImportError::CapturingGroup(2))
+        );
+        assert_eq!(
+            import_path_to_regexp("/:id}", V6),
+            Err(ImportError::Unexpected('}', 4))
+        );
+    }
+}
@@ -0,0 +1,287 @@
+// SPDX-License-Identifier: MIT
+
+//! Matching of the search component as a set of parameters.
+//!
+//! The search component of a [`UrlPattern`](crate::UrlPattern) is matched as a
+//! single string, so `a=1&b=2` doesn't match `b=2&a=1`. A [`SearchPattern`]
+//! instead has a pattern for the value of every key and matches the
+//! parameters in any order. It is meant to be matched against the search
+//! input of a match result, with the search component itself left as `*`.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+use thiserror::Error;
+
+use crate::{Component, ComponentResult, Options, ParseError};
+
+#[derive(Error, Debug)]
+pub enum SearchPatternError {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error("duplicate key `{0}`")]
+    DuplicateKey(String),
+}
+
+/// Options for compiling a [`SearchPattern`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchPatternOptions {
+    pub ignore_case: bool,
+    /// Whether an input with keys that have no pattern is rejected.
+    pub deny_extra_keys: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Param {
+    key: String,
+    value: Component,
+    /// The name of every group of `value` in the match result.
+    group_names: Vec<(String, String)>,
+}
+
+/// Patterns for the values of search parameters, matched regardless of the
+/// order of the parameters.
+#[derive(Debug, Clone)]
+pub struct SearchPattern {
+    params: Vec<Param>,
+    ignore_case: bool,
+    deny_extra_keys: bool,
+}
+
+/// Splits `input` on the `&` that aren't escaped or inside of a regexp group.
+fn split_params(input: &str) -> Vec<&str> {
+    let mut params = vec![];
+    let mut start = 0;
+    let mut depth = 0usize;
+    let mut chars = input.char_indices();
+    while let Some((index, chr)) = chars.next() {
+        match chr {
+            '\\' => {
+                chars.next();
+            }
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            '&' if depth == 0 => {
+                params.push(&input[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    params.push(&input[start..]);
+    params
+}
+
+/// Decodes a key or value like `application/x-www-form-urlencoded` does:
+/// `+` is a space and `%` followed by two hex digits is a byte.
+///
+/// <https://url.spec.whatwg.org/#urlencoded-parsing>
+fn decode(input: &str) -> Cow<'_, str> {
+    if !input.contains(['+', '%']) {
+        return Cow::Borrowed(input);
+    }
+    let hex = |byte: u8| char::from(byte).to_digit(16);
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let byte = match bytes[index] {
+            b'+' => b' ',
+            b'%' => match (bytes.get(index + 1), bytes.get(index + 2)) {
+                (Some(&high), Some(&low)) if hex(high).is_some() && hex(low).is_some() => {
+                    index += 2;
+                    (hex(high).unwrap() * 16 + hex(low).unwrap()) as u8
+                }
+                _ => b'%',
+            },
+            byte => byte,
+        };
+        decoded.push(byte);
+        index += 1;
+    }
+    Cow::Owned(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+impl SearchPattern {
+    /// Compiles a pattern for every key, given as `(key, value pattern)`.
+    ///
+    /// A key that is missing from the input is matched as if its value was
+    /// the empty string, so it is only optional if its pattern matches the
+    /// empty string, like `(\d+)?`. Unnamed groups are named after their key:
+    /// the first one `key`, the next ones `key.1`, `key.2` and so on.
+    pub fn new<'a>(
+        params: impl IntoIterator<Item = (&'a str, &'a str)>,
+        options: &SearchPatternOptions,
+    ) -> Result<SearchPattern, SearchPatternError> {
+        let component_options = Options {
+            ignore_case: options.ignore_case,
+            ..Options::default()
+        };
+
+        let mut keys = HashSet::new();
+        let mut names = HashSet::new();
+        let mut compiled = vec![];
+        for (key, value) in params {
+            if !keys.insert(key) {
+                return Err(SearchPatternError::DuplicateKey(key.into()));
+            }
+
+            let value = Component::compile(value, &component_options)?;
+            let mut group_names = vec![];
+            for name in value.group_names() {
+                let renamed = match name.parse::<usize>() {
+                    Ok(0) => key.to_string(),
+                    Ok(index) => format!("{key}.{index}"),
+                    Err(_) => name.clone(),
+                };
+                if !names.insert(renamed.clone()) {
+                    return Err(ParseError::DuplicateName(renamed).into());
+                }
+                group_names.push((name.clone(), renamed));
+            }
+
+            compiled.push(Param {
+                key: key.into(),
+                value,
+                group_names,
+            });
+        }
+
+        Ok(SearchPattern {
+            params: compiled,
+            ignore_case: options.ignore_case,
+            deny_extra_keys: options.deny_extra_keys,
+        })
+    }
+
+    /// Compiles a pattern like `q=:term&page=(\d+)?`.
+    ///
+    /// The text before the first `=` of every parameter is its key. A `&`
+    /// inside of a regexp group or escaped with `\` doesn't separate
+    /// parameters.
+    pub fn parse(
+        input: &str,
+        options: &SearchPatternOptions,
+    ) -> Result<SearchPattern, SearchPatternError> {
+        let input = input.strip_prefix('?').unwrap_or(input);
+        let params = split_params(input)
+            .into_iter()
+            .filter(|param| !param.is_empty())
+            .map(|param| param.split_once('=').unwrap_or((param, "")));
+        SearchPattern::new(params, options)
+    }
+
+    /// The keys with a pattern, in the order they were given.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.params.iter().map(|param| param.key.as_str())
+    }
+
+    /// Matches a search string, with or without the leading `?`.
+    ///
+    /// Keys and values are decoded like `application/x-www-form-urlencoded`
+    /// before they are matched, so `q=a+b` and `q=a%20b` both match `q=a b`
+    /// and capture `a b`. Only the first parameter with a key is matched
+    /// against its pattern; any further ones count as extra keys.
+    pub fn exec(&self, input: &str) -> Option<ComponentResult> {
+        let search = input.strip_prefix('?').unwrap_or(input);
+        let mut params: Vec<(Cow<str>, Cow<str>)> = search
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .map(|param| param.split_once('=').unwrap_or((param, "")))
+            .map(|(key, value)| (decode(key), decode(value)))
+            .collect();
+
+        let mut groups = HashMap::new();
+        for param in &self.params {
+            let position = params.iter().position(|(key, _)| {
+                if self.ignore_case {
+                    key.eq_ignore_ascii_case(&param.key)
+                } else {
+                    *key == param.key
+                }
+            });
+            let value = position.map_or(Cow::Borrowed(""), |position| params.remove(position).1);
+
+            let mut result = param.value.exec(&value)?;
+            for (name, renamed) in &param.group_names {
+                groups.insert(renamed.clone(), result.groups.remove(name).flatten());
+            }
+        }
+
+        if self.deny_extra_keys && !params.is_empty() {
+            return None;
+        }
+
+        Some(ComponentResult {
+            input: input.to_string(),
+            groups,
+        })
+    }
+
+    /// Returns whether a search string matches.
+    pub fn test(&self, input: &str) -> bool {
+        self.exec(input).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_pattern() {
+        let pattern =
+            SearchPattern::parse(r"?q=:term&page=(\d+)?", &SearchPatternOptions::default())
+                .unwrap();
+        assert_eq!(pattern.keys().collect::<Vec<_>>(), ["q", "page"]);
+
+        let result = pattern.exec("?page=2&q=rust&lang=en").unwrap();
+        assert_eq!(result.groups["term"].as_deref(), Some("rust"));
+        assert_eq!(result.groups["page"].as_deref(), Some("2"));
+        assert!(pattern.test("q=rust"));
+        assert!(!pattern.test("page=2"));
+        assert!(!pattern.test("q=rust&page=two"));
+
+        let strict = SearchPattern::parse(
+            "q=:term",
+            &SearchPatternOptions {
+                deny_extra_keys: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(strict.test("q=rust"));
+        assert!(!strict.test("q=rust&lang=en"));
+        assert!(!strict.test("q=rust&q=go"));
+
+        assert!(matches!(
+            SearchPattern::parse("a=:x&b=:x", &SearchPatternOptions::default()),
+            Err(SearchPatternError::Parse(ParseError::DuplicateName(_)))
+        ));
+        assert!(matches!(
+            SearchPattern::parse("a=1&a=2", &SearchPatternOptions::default()),
+            Err(SearchPatternError::DuplicateKey(_))
+        ));
+    }
+
+    #[test]
+    fn form_urlencoded() {
+        let pattern =
+            SearchPattern::parse(r"q=:term&page=(\d+)?", &SearchPatternOptions::default()).unwrap();
+        let result = pattern.exec("q=hello%20world").unwrap();
+        assert_eq!(result.groups["term"].as_deref(), Some("hello world"));
+        assert_eq!(result.input, "q=hello%20world");
+        let result = pattern.exec("q=hello+world&page=%32").unwrap();
+        assert_eq!(result.groups["term"].as_deref(), Some("hello world"));
+        assert_eq!(result.groups["page"].as_deref(), Some("2"));
+
+        // Keys are decoded as well.
+        assert!(pattern.test("%71=x"));
+
+        // Invalid escapes are kept and invalid UTF-8 is replaced.
+        let result = pattern.exec("q=100%&page=").unwrap();
+        assert_eq!(result.groups["term"].as_deref(), Some("100%"));
+        let result = pattern.exec("q=%FF%e2%82%ac").unwrap();
+        assert_eq!(result.groups["term"].as_deref(), Some("\u{FFFD}€"));
+    }
+}
@@ -0,0 +1,163 @@
+// SPDX-License-Identifier: MIT
+
+//! Serde support for [`UrlPattern`].
+//!
+//! A pattern deserializes from either a constructor string or a map of
+//! component pattern strings, like [`UrlPatternInit`]. It serializes to the
+//! map of the normalized pattern strings of every component.
+//!
+//! Unknown keys in the map are an error, so a misspelled component doesn't
+//! silently fall back to `*`.
+
+use std::fmt;
+
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, Error as _, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{UrlPattern, UrlPatternInit, UrlPatternOptions};
+
+/// The fields of [`UrlPatternInit`] and the options. `#[serde(flatten)]`
+/// can't be combined with `deny_unknown_fields`, so they are listed here.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+struct PatternMap {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    protocol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hostname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    port: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pathname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    search: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash: Option<String>,
+    #[serde(rename = "baseURL", skip_serializing_if = "Option::is_none")]
+    base_url: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    ignore_case: bool,
+}
+
+impl PatternMap {
+    fn new(init: UrlPatternInit, ignore_case: bool) -> PatternMap {
+        PatternMap {
+            protocol: init.protocol,
+            username: init.username,
+            password: init.password,
+            hostname: init.hostname,
+            port: init.port,
+            pathname: init.pathname,
+            search: init.search,
+            hash: init.hash,
+            base_url: init.base_url,
+            ignore_case,
+        }
+    }
+
+    fn init(self) -> UrlPatternInit {
+        UrlPatternInit {
+            protocol: self.protocol,
+            username: self.username,
+            password: self.password,
+            hostname: self.hostname,
+            port: self.port,
+            pathname: self.pathname,
+            search: self.search,
+            hash: self.hash,
+            base_url: self.base_url,
+        }
+    }
+}
+
+struct PatternVisitor;
+
+impl<'de> Visitor<'de> for PatternVisitor {
+    type Value = UrlPattern;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a constructor string or a map of URL components")
+    }
+
+    fn visit_str<E: de::Error>(self, input: &str) -> Result<UrlPattern, E> {
+        UrlPattern::parse(input, None, &UrlPatternOptions::default()).map_err(E::custom)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<UrlPattern, A::Error> {
+        let map = PatternMap::deserialize(MapAccessDeserializer::new(map))?;
+        let options = UrlPatternOptions {
+            ignore_case: map.ignore_case,
+        };
+        UrlPattern::from_init(&map.init(), &options).map_err(A::Error::custom)
+    }
+}
+
+impl Serialize for UrlPattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PatternMap::new(self.to_init(), self.ignore_case()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for UrlPattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(PatternVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{UrlPattern, UrlPatternInit};
+
+    #[test]
+    fn round_trip() {
+        let pattern: UrlPattern =
+            serde_json::from_str(r#""https://example.com/users/:id(\\d+)""#).unwrap();
+        let json = serde_json::to_value(&pattern).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "protocol": "https",
+                "username": "*",
+                "password": "*",
+                "hostname": "example.com",
+                "port": "",
+                "pathname": "/users/:id(\\d+)",
+                "search": "*",
+                "hash": "*",
+            })
+        );
+
+        let pattern: UrlPattern = serde_json::from_value(json).unwrap();
+        let result = pattern.exec_url("https://example.com/users/42").unwrap();
+        assert_eq!(
+            serde_json::to_value(&result.pathname).unwrap(),
+            serde_json::json!({ "input": "/users/42", "groups": { "id": "42" } })
+        );
+
+        let pattern: UrlPattern = serde_json::from_str(
+            r#"{ "pathname": "/static/*", "baseURL": "https://cdn.example.com", "ignoreCase": true }"#,
+        )
+        .unwrap();
+        assert!(pattern.test_url("https://cdn.example.com/STATIC/app.js"));
+        assert_eq!(serde_json::to_value(&pattern).unwrap()["ignoreCase"], true);
+
+        let err = serde_json::from_str::<UrlPattern>(r#"{ "pathname": "/:id(" }"#).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid pathname pattern \"/:id(\": missing one or more closing parentheses `)` in regular expression at line 1 column 23"
+        );
+    }
+
+    #[test]
+    fn unknown_keys() {
+        // A misspelled component must not turn into a `*` that matches everything.
+        let err = serde_json::from_str::<UrlPattern>(r#"{ "pathnme": "/admin/*" }"#).unwrap_err();
+        assert!(err.to_string().starts_with("unknown field `pathnme`"));
+        assert!(serde_json::from_str::<UrlPatternInit>(r#"{ "pathnme": "/admin/*" }"#).is_err());
+    }
+}
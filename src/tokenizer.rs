@@ -1,120 +1,259 @@
 // SPDX-License-Identifier: MIT
 
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
 use crate::ParseError;
 
-/// <https://urlpattern.spec.whatwg.org/#token>
-#[derive(Clone, Debug)]
-pub(crate) enum Token {
+/// <https://urlpattern.spec.whatwg.org/#token-type>
+///
+/// Names and regexps borrow from the tokenized input.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum TokenKind<'a> {
     Open,
     Close,
-    RegExp(String),
-    Name(String),
+    RegExp(&'a str),
+    Name(&'a str),
+    Constraint(&'a str),
     Char(char),
     EscapedChar(char),
-    Plus,         /// a.k.a. "other-modifier"
-    QuestionMark, /// a.k.a. "other-modifier"
+    Plus,         // a.k.a. "other-modifier"
+    QuestionMark, // a.k.a. "other-modifier"
     Asterisk,
     End,
-    InvalidChar,
+    #[cfg_attr(not(feature = "regex"), allow(dead_code))]
+    InvalidChar(char),
+}
+
+/// <https://urlpattern.spec.whatwg.org/#token>
+#[derive(Clone, Debug)]
+pub(crate) struct Token<'a> {
+    pub(crate) kind: TokenKind<'a>,
+    /// The byte range of the token in the input.
+    #[cfg_attr(not(feature = "regex"), allow(dead_code))]
+    pub(crate) span: Range<usize>,
 }
 
 /// <https://urlpattern.spec.whatwg.org/#tokenize-policy>
 pub(crate) enum Policy {
     Strict,
-    _Lenient,
+    #[cfg_attr(not(feature = "regex"), allow(dead_code))]
+    Lenient,
 }
 
-pub(crate) fn tokenize(input: &str, _policy: Policy) -> Result<Vec<Token>, ParseError> {
+/// <https://urlpattern.spec.whatwg.org/#is-a-valid-name-code-point>
+pub(crate) fn is_valid_name_code_point(chr: char, first: bool) -> bool {
+    // TODO: Use the real ID_Start and ID_Continue properties.
+    if first {
+        chr == '$' || chr == '_' || chr.is_alphabetic()
+    } else {
+        chr == '$' || chr == '_' || chr == '\u{200C}' || chr == '\u{200D}' || chr.is_alphanumeric()
+    }
+}
+
+/// <https://urlpattern.spec.whatwg.org/#tokenize>
+///
+/// If `constraints` is true, a `<name>` right after a name is a constraint
+/// token instead of fixed text.
+pub(crate) fn tokenize(
+    input: &str,
+    policy: Policy,
+    constraints: bool,
+) -> Result<Vec<Token<'_>>, ParseError> {
     let mut tokens = vec![];
+    let chars: Vec<(usize, char)> = input.char_indices().collect();
+    // The byte index of the code point at `position`.
+    let offset = |position: usize| chars.get(position).map_or(input.len(), |&(index, _)| index);
+    let mut position = 0;
 
-    let mut iter = input.chars().peekable();
-    while let Some(chr) = iter.next() {
-        match chr {
-            // If tokenizer’s code point is U+002A (*):
-            '*' => {
-                // Run add a token with default position and length given tokenizer and "asterisk".
-                tokens.push(Token::Asterisk);
+    while let Some(&(index, chr)) = chars.get(position) {
+        let start = position;
+        position += 1;
+
+        // https://urlpattern.spec.whatwg.org/#process-a-tokenizing-error
+        // Continues tokenizing after the code point that caused the error.
+        let mut error = |error: ParseError| match policy {
+            Policy::Strict => Err(error),
+            Policy::Lenient => {
+                tokens.push(Token {
+                    kind: TokenKind::InvalidChar(chr),
+                    span: index..offset(start + 1),
+                });
+                Ok(start + 1)
             }
+        };
+
+        let kind = match chr {
+            // If tokenizer’s code point is U+002A (*):
+            // Run add a token with default position and length given tokenizer and "asterisk".
+            '*' => TokenKind::Asterisk,
             // If tokenizer’s code point is U+002B (+) or U+003F (?):
-            '+' => {
-                // Run add a token with default position and length given tokenizer and "other-modifier".
-                tokens.push(Token::Plus);
-            }
-            '?' => {
-                // Run add a token with default position and length given tokenizer and "other-modifier".
-                tokens.push(Token::QuestionMark);
-            }
+            // Run add a token with default position and length given tokenizer and "other-modifier".
+            '+' => TokenKind::Plus,
+            '?' => TokenKind::QuestionMark,
             // If tokenizer’s code point is U+005C (\):
             '\\' => {
-                unimplemented!("EscapedChar");
+                // If tokenizer’s index is equal to tokenizer’s input's code point length − 1:
+                // Run process a tokenizing error given tokenizer, tokenizer’s next index, and tokenizer’s index.
+                match chars.get(position) {
+                    Some(&(_, escaped)) => {
+                        position += 1;
+                        TokenKind::EscapedChar(escaped)
+                    }
+                    None => {
+                        position = error(ParseError::TrailingBackslash)?;
+                        continue;
+                    }
+                }
             }
             // If tokenizer’s code point is U+007B ({):
-            '{' => {
-                // Run add a token with default position and length given tokenizer and "open".
-                tokens.push(Token::Open);
-            }
+            // Run add a token with default position and length given tokenizer and "open".
+            '{' => TokenKind::Open,
             // If tokenizer’s code point is U+007D (}):
-            '}' => {
-                // Run add a token with default position and length given tokenizer and "close".
-                tokens.push(Token::Close)
-            }
+            // Run add a token with default position and length given tokenizer and "close".
+            '}' => TokenKind::Close,
             // If tokenizer’s code point is U+003A (:):
             ':' => {
-                let mut name = String::new();
-                while let Some(chr) = iter.peek() {
-                    match chr {
-                        // TODO: correct this.
-                        'A'..='Z' | 'a'..='z' => name.push(iter.next().unwrap()),
-                        _ => break,
+                let name_start = position;
+                while let Some(&(_, chr)) = chars.get(position) {
+                    if !is_valid_name_code_point(chr, position == name_start) {
+                        break;
                     }
+                    position += 1;
                 }
-                tokens.push(Token::Name(name))
+
+                // If name start is equal to name position:
+                // Run process a tokenizing error given tokenizer, name start, and tokenizer’s index.
+                if position == name_start {
+                    position = error(ParseError::MissingName)?;
+                    continue;
+                }
+                let name = &input[offset(name_start)..offset(position)];
+                let name_end = position;
+
+                let mut constraint = None;
+                if constraints && chars.get(position).map(|&(_, chr)| chr) == Some('<') {
+                    let constraint_start = position + 1;
+                    let mut end = constraint_start;
+                    while let Some(&(_, chr)) = chars.get(end) {
+                        if !is_valid_name_code_point(chr, end == constraint_start) {
+                            break;
+                        }
+                        end += 1;
+                    }
+
+                    if end == constraint_start || chars.get(end).map(|&(_, chr)| chr) != Some('>') {
+                        position = error(ParseError::UnclosedConstraint)?;
+                        continue;
+                    }
+
+                    constraint = Some(Token {
+                        kind: TokenKind::Constraint(&input[offset(constraint_start)..offset(end)]),
+                        span: offset(name_end)..offset(end + 1),
+                    });
+                    position = end + 1;
+                }
+
+                tokens.push(Token {
+                    kind: TokenKind::Name(name),
+                    span: index..offset(name_end),
+                });
+                tokens.extend(constraint);
+                continue;
             }
             // 8. If tokenizer’s code point is U+0028 (():
             '(' => {
                 let mut depth = 1;
+                let regexp_start = position;
+                let mut regexp_end = position;
+                let mut failure = None;
 
-                // TODO: this is totally not implemented.
+                while let Some(&(_, chr)) = chars.get(position) {
+                    position += 1;
 
-                let mut regexp = String::new();
-                while let Some(chr) = iter.next() {
+                    // If the result of running is ASCII given tokenizer’s code point is false:
                     if !chr.is_ascii() {
-                        todo!();
+                        failure = Some(ParseError::NonAsciiRegExp);
+                        break;
                     }
 
-                    // todo 3,4
+                    // If regexp position equals regexp start and tokenizer’s code point is U+003F (?):
+                    if position - 1 == regexp_start && chr == '?' {
+                        failure = Some(ParseError::InvalidRegExpStart);
+                        break;
+                    }
 
                     match chr {
-                        '(' => {
-                            depth += 1
-                            // TODO!
+                        '\\' => {
+                            // Run get the next code point given tokenizer.
+                            match chars.get(position) {
+                                Some(&(_, escaped)) if escaped.is_ascii() => {
+                                    position += 1;
+                                    regexp_end = position;
+                                }
+                                Some(_) => {
+                                    failure = Some(ParseError::NonAsciiRegExp);
+                                    break;
+                                }
+                                None => {
+                                    failure = Some(ParseError::TrailingBackslash);
+                                    break;
+                                }
+                            }
+                            continue;
                         }
                         ')' => {
                             depth -= 1;
                             if depth == 0 {
-                                // Set regexp position to tokenizer’s next index.???
+                                break;
+                            }
+                        }
+                        '(' => {
+                            depth += 1;
+
+                            // If temporary position's code point is not U+003F (?):
+                            // Run process a tokenizing error given tokenizer, regexp start, and tokenizer’s index.
+                            if chars.get(position).map(|&(_, chr)| chr) != Some('?') {
+                                failure = Some(ParseError::CapturingGroupInRegExp);
                                 break;
                             }
                         }
                         _ => {}
                     }
 
-                    regexp.push(chr);
+                    regexp_end = position;
                 }
 
-                if depth != 0 {
-                    return Err(ParseError::ParenthesesMissmatch);
+                if failure.is_none() {
+                    // If depth is not zero:
+                    if depth != 0 {
+                        failure = Some(ParseError::ParenthesesMissmatch);
+                    // If regexp length is zero:
+                    } else if regexp_end == regexp_start {
+                        failure = Some(ParseError::EmptyRegExp);
+                    }
                 }
 
-                tokens.push(Token::RegExp(regexp))
-            }
-            _ => {
-                // TODO
-                tokens.push(Token::Char(chr));
+                if let Some(failure) = failure {
+                    position = error(failure)?;
+                    continue;
+                }
+                TokenKind::RegExp(&input[offset(regexp_start)..offset(regexp_end)])
             }
-        }
+            // TODO
+            _ => TokenKind::Char(chr),
+        };
+
+        tokens.push(Token {
+            kind,
+            span: index..offset(position),
+        });
     }
 
-    tokens.push(Token::End);
+    tokens.push(Token {
+        kind: TokenKind::End,
+        span: input.len()..input.len(),
+    });
     Ok(tokens)
 }
@@ -0,0 +1,353 @@
+// SPDX-License-Identifier: MIT
+
+//! A [`tower::Service`] that routes [`http::Request`] values by [`UrlPattern`].
+
+use std::task::{Context, Poll};
+
+use ::http::{Method, Request};
+use ::tower::util::{BoxCloneService, Oneshot, ServiceExt};
+use ::tower::{Layer, Service};
+
+use crate::UrlPattern;
+
+struct Route<B, Res, E> {
+    pattern: UrlPattern,
+    methods: Option<Vec<Method>>,
+    service: BoxCloneService<Request<B>, Res, E>,
+}
+
+impl<B, Res, E> Clone for Route<B, Res, E> {
+    fn clone(&self) -> Self {
+        Route {
+            pattern: self.pattern.clone(),
+            methods: self.methods.clone(),
+            service: self.service.clone(),
+        }
+    }
+}
+
+/// Dispatches every request to the service of the first route whose pattern
+/// matches the request, or to the fallback service if none does.
+///
+/// The [`UrlPatternResult`](crate::UrlPatternResult) of the matching route is
+/// inserted into the extensions of the request. Requests are matched with
+/// [`UrlPattern::exec_request_with_scheme`], with the scheme set by
+/// [`PatternRouter::scheme`] for URIs without one.
+///
+/// ```
+/// use std::convert::Infallible;
+///
+/// use http::{Method, Request};
+/// use tower::service_fn;
+/// use url_pattern::{PatternRouter, UrlPattern, UrlPatternOptions};
+///
+/// async fn respond(_request: Request<()>) -> Result<&'static str, Infallible> {
+///     Ok("")
+/// }
+///
+/// let options = UrlPatternOptions::default();
+/// let users = UrlPattern::parse("https://example.com/users/:id", None, &options)?;
+/// let upload = UrlPattern::parse("https://example.com/upload", None, &options)?;
+/// let router = PatternRouter::new(service_fn(respond))
+///     .route(users, service_fn(respond))
+///     .route_methods([Method::POST], upload, service_fn(respond));
+/// # Ok::<(), url_pattern::UrlPatternError>(())
+/// ```
+pub struct PatternRouter<B, Res, E> {
+    layer: PatternRouterLayer<B, Res, E>,
+    fallback: BoxCloneService<Request<B>, Res, E>,
+}
+
+impl<B, Res, E> Clone for PatternRouter<B, Res, E> {
+    fn clone(&self) -> Self {
+        PatternRouter {
+            layer: self.layer.clone(),
+            fallback: self.fallback.clone(),
+        }
+    }
+}
+
+impl<B, Res, E> PatternRouter<B, Res, E> {
+    /// Creates a router without routes, which sends every request to
+    /// `fallback`.
+    pub fn new<S>(fallback: S) -> Self
+    where
+        S: Service<Request<B>, Response = Res, Error = E> + Clone + Send + 'static,
+        S::Future: Send + 'static,
+    {
+        PatternRouterLayer::new().layer(fallback)
+    }
+
+    /// Sets the scheme that requests without one in their URI are matched
+    /// with, `http` by default.
+    ///
+    /// Use `https` for a router behind a TLS listener.
+    pub fn scheme(mut self, scheme: &str) -> Self {
+        self.layer = self.layer.scheme(scheme);
+        self
+    }
+
+    /// Adds a route for requests with any method.
+    pub fn route<S>(mut self, pattern: UrlPattern, service: S) -> Self
+    where
+        S: Service<Request<B>, Response = Res, Error = E> + Clone + Send + 'static,
+        S::Future: Send + 'static,
+    {
+        self.layer = self.layer.route(pattern, service);
+        self
+    }
+
+    /// Adds a route for requests with one of `methods`.
+    ///
+    /// A request with another method is matched against the following routes.
+    pub fn route_methods<S>(
+        mut self,
+        methods: impl IntoIterator<Item = Method>,
+        pattern: UrlPattern,
+        service: S,
+    ) -> Self
+    where
+        S: Service<Request<B>, Response = Res, Error = E> + Clone + Send + 'static,
+        S::Future: Send + 'static,
+    {
+        self.layer = self.layer.route_methods(methods, pattern, service);
+        self
+    }
+}
+
+/// A [`Layer`] that wraps a service into a [`PatternRouter`] with the routes
+/// of the layer, and the wrapped service as the fallback.
+///
+/// ```
+/// use std::convert::Infallible;
+///
+/// use http::Request;
+/// use tower::{service_fn, ServiceBuilder};
+/// use url_pattern::{PatternRouterLayer, UrlPattern, UrlPatternOptions};
+///
+/// async fn respond(_request: Request<()>) -> Result<&'static str, Infallible> {
+///     Ok("")
+/// }
+///
+/// let options = UrlPatternOptions::default();
+/// let pattern = UrlPattern::parse("https://example.com/users/:id", None, &options)?;
+/// let service = ServiceBuilder::new()
+///     .layer(PatternRouterLayer::new().route(pattern, service_fn(respond)))
+///     .service(service_fn(respond));
+/// # Ok::<(), url_pattern::UrlPatternError>(())
+/// ```
+pub struct PatternRouterLayer<B, Res, E> {
+    routes: Vec<Route<B, Res, E>>,
+    scheme: String,
+}
+
+impl<B, Res, E> Clone for PatternRouterLayer<B, Res, E> {
+    fn clone(&self) -> Self {
+        PatternRouterLayer {
+            routes: self.routes.clone(),
+            scheme: self.scheme.clone(),
+        }
+    }
+}
+
+impl<B, Res, E> Default for PatternRouterLayer<B, Res, E> {
+    fn default() -> Self {
+        PatternRouterLayer::new()
+    }
+}
+
+impl<B, Res, E> PatternRouterLayer<B, Res, E> {
+    /// Creates a layer without routes.
+    pub fn new() -> Self {
+        PatternRouterLayer {
+            routes: vec![],
+            scheme: "http".into(),
+        }
+    }
+
+    /// Like [`PatternRouter::scheme`].
+    pub fn scheme(mut self, scheme: &str) -> Self {
+        self.scheme = scheme.into();
+        self
+    }
+
+    /// Like [`PatternRouter::route`].
+    pub fn route<S>(self, pattern: UrlPattern, service: S) -> Self
+    where
+        S: Service<Request<B>, Response = Res, Error = E> + Clone + Send + 'static,
+        S::Future: Send + 'static,
+    {
+        self.add_route(pattern, None, service)
+    }
+
+    /// Like [`PatternRouter::route_methods`].
+    pub fn route_methods<S>(
+        self,
+        methods: impl IntoIterator<Item = Method>,
+        pattern: UrlPattern,
+        service: S,
+    ) -> Self
+    where
+        S: Service<Request<B>, Response = Res, Error = E> + Clone + Send + 'static,
+        S::Future: Send + 'static,
+    {
+        self.add_route(pattern, Some(methods.into_iter().collect()), service)
+    }
+
+    fn add_route<S>(mut self, pattern: UrlPattern, methods: Option<Vec<Method>>, service: S) -> Self
+    where
+        S: Service<Request<B>, Response = Res, Error = E> + Clone + Send + 'static,
+        S::Future: Send + 'static,
+    {
+        self.routes.push(Route {
+            pattern,
+            methods,
+            service: BoxCloneService::new(service),
+        });
+        self
+    }
+}
+
+impl<S, B, Res, E> Layer<S> for PatternRouterLayer<B, Res, E>
+where
+    S: Service<Request<B>, Response = Res, Error = E> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Service = PatternRouter<B, Res, E>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        PatternRouter {
+            layer: self.clone(),
+            fallback: BoxCloneService::new(inner),
+        }
+    }
+}
+
+impl<B, Res, E> Service<Request<B>> for PatternRouter<B, Res, E> {
+    type Response = Res;
+    type Error = E;
+    type Future = Oneshot<BoxCloneService<Request<B>, Res, E>, Request<B>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), E>> {
+        // The inner services are driven to readiness by `Oneshot` once the
+        // request is dispatched to one of them.
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        for route in &self.layer.routes {
+            if route
+                .methods
+                .as_ref()
+                .is_some_and(|methods| !methods.contains(request.method()))
+            {
+                continue;
+            }
+            if let Some(result) = route
+                .pattern
+                .exec_request_with_scheme(&request, &self.layer.scheme)
+            {
+                request.extensions_mut().insert(result);
+                return route.service.clone().oneshot(request);
+            }
+        }
+        self.fallback.clone().oneshot(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use ::http::{Method, Request};
+    use ::tower::{service_fn, ServiceBuilder, ServiceExt};
+
+    use super::{PatternRouter, PatternRouterLayer};
+    use crate::{UrlPattern, UrlPatternOptions, UrlPatternResult};
+
+    fn pattern(input: &str) -> UrlPattern {
+        UrlPattern::parse(input, None, &UrlPatternOptions::default()).unwrap()
+    }
+
+    fn respond(
+        name: &'static str,
+    ) -> impl Fn(Request<()>) -> std::future::Ready<Result<String, Infallible>> + Clone {
+        move |request: Request<()>| {
+            let id = request
+                .extensions()
+                .get::<UrlPatternResult>()
+                .and_then(|result| result.pathname.groups.get("id").cloned().flatten());
+            std::future::ready(Ok(format!("{name} {}", id.unwrap_or_default())))
+        }
+    }
+
+    #[tokio::test]
+    async fn router() {
+        let router = PatternRouter::new(service_fn(respond("fallback")))
+            .route_methods(
+                [Method::POST],
+                pattern("http://example.com/users/:id"),
+                service_fn(respond("update")),
+            )
+            .route(
+                pattern("http://example.com/users/:id"),
+                service_fn(respond("show")),
+            );
+
+        let call = |method: Method, uri: &str| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("host", "example.com")
+                .body(())
+                .unwrap();
+            router.clone().oneshot(request)
+        };
+
+        assert_eq!(call(Method::GET, "/users/7").await.unwrap(), "show 7");
+        assert_eq!(call(Method::POST, "/users/7").await.unwrap(), "update 7");
+        assert_eq!(call(Method::GET, "/posts/7").await.unwrap(), "fallback ");
+    }
+
+    #[tokio::test]
+    async fn scheme() {
+        let router = PatternRouter::new(service_fn(respond("fallback")))
+            .route(
+                pattern("https://example.com/users/:id"),
+                service_fn(respond("show")),
+            )
+            .scheme("https");
+
+        let request = Request::get("/users/7")
+            .header("host", "example.com")
+            .body(())
+            .unwrap();
+        assert_eq!(router.clone().oneshot(request).await.unwrap(), "show 7");
+
+        let request = Request::get("http://example.com/users/7").body(()).unwrap();
+        assert_eq!(router.oneshot(request).await.unwrap(), "fallback ");
+    }
+
+    #[tokio::test]
+    async fn layer() {
+        let layer = PatternRouterLayer::new()
+            .route(
+                pattern("https://example.com/users/:id"),
+                service_fn(respond("show")),
+            )
+            .scheme("https");
+        let router = ServiceBuilder::new()
+            .layer(layer)
+            .service(service_fn(respond("inner")));
+
+        let call = |uri: &str| {
+            let request = Request::get(uri)
+                .header("host", "example.com")
+                .body(())
+                .unwrap();
+            router.clone().oneshot(request)
+        };
+        assert_eq!(call("/users/7").await.unwrap(), "show 7");
+        assert_eq!(call("/posts/7").await.unwrap(), "inner ");
+    }
+}
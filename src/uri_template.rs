@@ -0,0 +1,423 @@
+// SPDX-License-Identifier: MIT
+
+//! Conversion between URL pattern parts and [RFC 6570](https://www.rfc-editor.org/rfc/rfc6570)
+//! URI Templates.
+//!
+//! A template describes how to expand variables into a URL, so constraints
+//! like regexp groups are dropped when converting into a template. Only the
+//! pathname and search components have an equivalent.
+
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::{Modifier, Part};
+
+/// A construct that has no equivalent on the other side of the conversion.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum UriTemplateError {
+    #[error("invalid URI template syntax at index {0}")]
+    Syntax(usize),
+    #[error("{0:?} is not allowed in a URI template literal")]
+    Literal(char),
+    #[error("`{0}` has a modifier without an equivalent")]
+    Modifier(String),
+    #[error("`{0}` has a prefix or suffix without an equivalent")]
+    Affix(String),
+    #[error("the search parts are not a list of `name=:name` pairs")]
+    Search,
+    #[error("the `{0}` operator has no equivalent")]
+    Operator(char),
+    #[error("the prefix modifier of `{0}` has no equivalent")]
+    PrefixModifier(String),
+    #[error("the explode modifier of `{0}` has no equivalent without a separator")]
+    Explode(String),
+    #[error("`{0}` has to be repeated at least once, which has no equivalent")]
+    OneOrMore(String),
+    #[error("variable name `{0}` is not a valid group name")]
+    Name(String),
+}
+
+/// Every construct of a conversion that has no equivalent.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub struct UriTemplateReport {
+    pub errors: Vec<UriTemplateError>,
+}
+
+impl fmt::Display for UriTemplateReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, error) in self.errors.iter().enumerate() {
+            if index > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{error}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The pathname and search parts of a URI Template.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UriTemplateParts {
+    pub pathname: Vec<Part>,
+    pub search: Vec<Part>,
+}
+
+fn push_literal(template: &mut String, value: &str, errors: &mut Vec<UriTemplateError>) {
+    for chr in value.chars() {
+        // https://www.rfc-editor.org/rfc/rfc6570#section-2.1
+        if chr.is_control() || " \"'<>\\^`{|}".contains(chr) {
+            errors.push(UriTemplateError::Literal(chr));
+        } else {
+            template.push(chr);
+        }
+    }
+}
+
+fn push_fixed(parts: &mut Vec<Part>, value: &str) {
+    if let Some(Part::FixedText {
+        value: previous,
+        modifier: None,
+    }) = parts.last_mut()
+    {
+        previous.push_str(value);
+    } else if !value.is_empty() {
+        parts.push(Part::FixedText {
+            value: value.into(),
+            modifier: None,
+        });
+    }
+}
+
+/// Converts the parts of a pathname and a search pattern into a URI Template.
+///
+/// Segment wildcards and regexp groups prefixed with `/` or `.` become path
+/// segment and label expansions, exploded when they are repeated zero or
+/// more times. An exploded expansion can also expand to nothing, so groups
+/// that are repeated one or more times are rejected. Full wildcards become
+/// reserved expansions. The search parts have to be a list
+/// of `name=:name` pairs separated by `&`, which become a query expansion.
+pub fn to_uri_template(pathname: &[Part], search: &[Part]) -> Result<String, UriTemplateReport> {
+    let mut template = String::new();
+    let mut errors = vec![];
+
+    for part in pathname {
+        match part {
+            Part::FixedText {
+                value,
+                modifier: None,
+            } => push_literal(&mut template, value, &mut errors),
+            Part::FixedText { value, .. } => errors.push(UriTemplateError::Modifier(value.clone())),
+            Part::SegmentWildcard {
+                name,
+                modifier,
+                prefix,
+                suffix,
+            }
+            | Part::RegExp {
+                name,
+                modifier,
+                prefix,
+                suffix,
+                ..
+            } => {
+                if *modifier == Some(Modifier::OneOrMore) {
+                    errors.push(UriTemplateError::OneOrMore(name.clone()));
+                    continue;
+                }
+                let repeated = *modifier == Some(Modifier::ZeroOrMore);
+                let explode = if repeated { "*" } else { "" };
+
+                if suffix.is_empty() && (prefix == "/" || prefix == ".") {
+                    template.push_str(&format!("{{{prefix}{name}{explode}}}"));
+                } else if prefix.is_empty() && suffix.is_empty() && !repeated {
+                    // An undefined variable expands to nothing, like an optional group.
+                    template.push_str(&format!("{{{name}}}"));
+                } else if modifier.is_none() {
+                    push_literal(&mut template, prefix, &mut errors);
+                    template.push_str(&format!("{{{name}}}"));
+                    push_literal(&mut template, suffix, &mut errors);
+                } else {
+                    errors.push(UriTemplateError::Affix(name.clone()));
+                }
+            }
+            Part::FullWildcard {
+                name,
+                modifier,
+                prefix,
+                suffix,
+            } => match modifier {
+                None => {
+                    push_literal(&mut template, prefix, &mut errors);
+                    template.push_str(&format!("{{+{name}}}"));
+                    push_literal(&mut template, suffix, &mut errors);
+                }
+                Some(Modifier::Optional) if prefix.is_empty() && suffix.is_empty() => {
+                    template.push_str(&format!("{{+{name}}}"));
+                }
+                Some(_) => errors.push(UriTemplateError::Modifier(name.clone())),
+            },
+        }
+    }
+
+    let mut names = vec![];
+    let mut pending = String::new();
+    for part in search {
+        match part {
+            Part::FixedText {
+                value,
+                modifier: None,
+            } => pending.push_str(value),
+            Part::SegmentWildcard {
+                name,
+                modifier: None | Some(Modifier::Optional),
+                prefix,
+                suffix,
+            }
+            | Part::RegExp {
+                name,
+                modifier: None | Some(Modifier::Optional),
+                prefix,
+                suffix,
+                ..
+            }
+            | Part::FullWildcard {
+                name,
+                modifier: None | Some(Modifier::Optional),
+                prefix,
+                suffix,
+            } if suffix.is_empty() => {
+                let separator = if names.is_empty() { "" } else { "&" };
+                if pending.clone() + prefix != format!("{separator}{name}=") {
+                    errors.push(UriTemplateError::Search);
+                    break;
+                }
+                names.push(name.as_str());
+                pending.clear();
+            }
+            _ => {
+                errors.push(UriTemplateError::Search);
+                break;
+            }
+        }
+    }
+    if !pending.is_empty() && !errors.contains(&UriTemplateError::Search) {
+        errors.push(UriTemplateError::Search);
+    }
+    if !names.is_empty() {
+        template.push_str(&format!("{{?{}}}", names.join(",")));
+    }
+
+    if !errors.is_empty() {
+        return Err(UriTemplateReport { errors });
+    }
+    Ok(template)
+}
+
+/// Converts a URI Template into the parts of a pathname and a search pattern.
+pub fn from_uri_template(template: &str) -> Result<UriTemplateParts, UriTemplateReport> {
+    let mut parts = UriTemplateParts::default();
+    let mut errors = vec![];
+    let mut in_search = false;
+
+    let mut offset = 0;
+    while let Some(chr) = template[offset..].chars().next() {
+        let index = offset;
+        offset += chr.len_utf8();
+
+        match chr {
+            '{' => {}
+            '}' => {
+                errors.push(UriTemplateError::Syntax(index));
+                continue;
+            }
+            // A literal `?` starts the search component.
+            '?' if !in_search => {
+                in_search = true;
+                continue;
+            }
+            _ => {
+                let mut literal = String::new();
+                push_literal(&mut literal, &chr.to_string(), &mut errors);
+                let target = if in_search {
+                    &mut parts.search
+                } else {
+                    &mut parts.pathname
+                };
+                push_fixed(target, &literal);
+                continue;
+            }
+        }
+
+        let Some(length) = template[offset..].find('}') else {
+            errors.push(UriTemplateError::Syntax(index));
+            break;
+        };
+        let expression = &template[offset..offset + length];
+        offset += length + 1;
+
+        // https://www.rfc-editor.org/rfc/rfc6570#section-2.2
+        let (operator, variables) = match expression.chars().next() {
+            Some(operator @ ('+' | '#' | '.' | '/' | ';' | '?' | '&')) => {
+                (Some(operator), &expression[1..])
+            }
+            Some(operator @ ('=' | ',' | '!' | '@' | '|')) => {
+                errors.push(UriTemplateError::Operator(operator));
+                continue;
+            }
+            _ => (None, expression),
+        };
+
+        match operator {
+            Some('#') => {
+                errors.push(UriTemplateError::Operator('#'));
+                continue;
+            }
+            Some('?' | '&') => in_search = true,
+            _ => {}
+        }
+
+        for (position, variable) in variables.split(',').enumerate() {
+            // https://www.rfc-editor.org/rfc/rfc6570#section-2.4
+            let (name, explode) = match variable.strip_suffix('*') {
+                Some(name) => (name, true),
+                None => (variable, false),
+            };
+            let name = match name.split_once(':') {
+                Some((name, _)) => {
+                    errors.push(UriTemplateError::PrefixModifier(name.into()));
+                    continue;
+                }
+                None => name,
+            };
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|chr| chr.is_ascii_alphanumeric() || "_.%".contains(chr))
+            {
+                errors.push(UriTemplateError::Syntax(index));
+                continue;
+            }
+            // Dots and percent-encoded triplets are allowed in variable
+            // names, but not in group names.
+            if name.contains(['.', '%']) {
+                errors.push(UriTemplateError::Name(name.into()));
+                continue;
+            }
+
+            let wildcard = |prefix: &str| Part::SegmentWildcard {
+                name: name.into(),
+                modifier: explode.then_some(Modifier::ZeroOrMore),
+                prefix: prefix.into(),
+                suffix: String::new(),
+            };
+
+            match operator {
+                // Simple and reserved expansion join the values with `,`.
+                None | Some('+') => {
+                    if explode {
+                        errors.push(UriTemplateError::Explode(name.into()));
+                        continue;
+                    }
+                    let prefix = if position > 0 { "," } else { "" };
+                    parts.pathname.push(if operator.is_some() {
+                        Part::FullWildcard {
+                            name: name.into(),
+                            modifier: None,
+                            prefix: prefix.into(),
+                            suffix: String::new(),
+                        }
+                    } else {
+                        wildcard(prefix)
+                    });
+                }
+                Some(prefix @ ('/' | '.')) => parts.pathname.push(wildcard(&prefix.to_string())),
+                Some(';') if explode => parts.pathname.push(wildcard(&format!(";{name}="))),
+                Some(';') => {
+                    push_fixed(&mut parts.pathname, &format!(";{name}="));
+                    parts.pathname.push(wildcard(""));
+                }
+                // Query expansion and continuation.
+                _ => {
+                    let separator = if operator == Some('?') && position == 0 {
+                        ""
+                    } else {
+                        "&"
+                    };
+                    if explode {
+                        if separator.is_empty() {
+                            errors.push(UriTemplateError::Explode(name.into()));
+                        } else {
+                            parts.search.push(wildcard(&format!("&{name}=")));
+                        }
+                        continue;
+                    }
+                    push_fixed(&mut parts.search, &format!("{separator}{name}="));
+                    parts.search.push(wildcard(""));
+                }
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(UriTemplateReport { errors });
+    }
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_pattern, Options};
+
+    #[test]
+    fn round_trip() {
+        let parts = from_uri_template("/users{/id}/files{/path*}{?q,lang}").unwrap();
+        assert_eq!(
+            parts.pathname,
+            parse_pattern("/users/:id/files/:path*", &Options::pathname()).unwrap()
+        );
+        assert_eq!(
+            parts.search,
+            parse_pattern("q=:q&lang=:lang", &Options::default()).unwrap()
+        );
+        assert_eq!(
+            to_uri_template(&parts.pathname, &parts.search).unwrap(),
+            "/users{/id}/files{/path*}{?q,lang}"
+        );
+
+        let pathname = parse_pattern("/static/*", &Options::pathname()).unwrap();
+        assert_eq!(to_uri_template(&pathname, &[]).unwrap(), "/static/{+0}");
+    }
+
+    #[test]
+    fn unsupported() {
+        let pathname = parse_pattern("/:a-:b*/{x}?/:c+", &Options::pathname()).unwrap();
+        assert_eq!(
+            to_uri_template(&pathname, &[]).unwrap_err().errors,
+            [
+                UriTemplateError::Affix("b".into()),
+                UriTemplateError::Modifier("x".into()),
+                UriTemplateError::OneOrMore("c".into())
+            ]
+        );
+
+        assert_eq!(
+            from_uri_template("/a{#frag}{x:3}{list*}")
+                .unwrap_err()
+                .errors,
+            [
+                UriTemplateError::Operator('#'),
+                UriTemplateError::PrefixModifier("x".into()),
+                UriTemplateError::Explode("list".into())
+            ]
+        );
+        assert_eq!(
+            from_uri_template("{/user.id}{?q%20x}").unwrap_err().errors,
+            [
+                UriTemplateError::Name("user.id".into()),
+                UriTemplateError::Name("q%20x".into())
+            ]
+        );
+    }
+}
@@ -0,0 +1,228 @@
+// SPDX-License-Identifier: MIT
+
+//! Matching of [`url::Url`] values, and canonicalization of the fixed text of
+//! patterns with the URL parser of the `url` crate.
+
+use ::url::Url;
+
+use crate::url_pattern::{Encoding, UrlComponents};
+use crate::{UrlPattern, UrlPatternError, UrlPatternInit, UrlPatternResult};
+
+/// Parses one of the dummy URLs the canonicalization steps run the URL
+/// parser on.
+fn dummy_url(base: &str) -> Url {
+    Url::parse(base).expect("valid dummy URL")
+}
+
+impl Encoding {
+    /// <https://urlpattern.spec.whatwg.org/#canon-encoding-callbacks>
+    pub(crate) fn canonicalize(self, value: &str) -> Result<String, UrlPatternError> {
+        // If value is the empty string, return value.
+        if value.is_empty() {
+            return Ok(String::new());
+        }
+
+        let error = |component| UrlPatternError::Canonicalize {
+            component,
+            value: value.into(),
+        };
+
+        match self {
+            // https://urlpattern.spec.whatwg.org/#canonicalize-a-protocol
+            Encoding::Protocol => Url::parse(&format!("{value}://dummy.invalid/"))
+                .map(|url| url.scheme().into())
+                .map_err(|_| error("protocol")),
+            // https://urlpattern.spec.whatwg.org/#canonicalize-a-username
+            Encoding::Username => {
+                let mut url = dummy_url("fake://dummy.invalid/");
+                url.set_username(value).map_err(|_| error("username"))?;
+                Ok(url.username().into())
+            }
+            // https://urlpattern.spec.whatwg.org/#canonicalize-a-password
+            Encoding::Password => {
+                let mut url = dummy_url("fake://dummy.invalid/");
+                url.set_password(Some(value))
+                    .map_err(|_| error("password"))?;
+                Ok(url.password().unwrap_or_default().into())
+            }
+            // https://urlpattern.spec.whatwg.org/#canonicalize-a-hostname
+            Encoding::Hostname | Encoding::OpaqueHostname => {
+                let mut url = dummy_url(match self {
+                    Encoding::Hostname => "http://dummy.invalid/",
+                    _ => "fake://dummy.invalid/",
+                });
+                url.set_host(Some(value)).map_err(|_| error("hostname"))?;
+                Ok(url.host_str().unwrap_or_default().into())
+            }
+            // https://urlpattern.spec.whatwg.org/#canonicalize-an-ipv6-hostname
+            Encoding::Ipv6Hostname => {
+                if !value
+                    .chars()
+                    .all(|chr| chr.is_ascii_hexdigit() || "[]:".contains(chr))
+                {
+                    return Err(error("hostname"));
+                }
+                Ok(value.to_ascii_lowercase())
+            }
+            // https://urlpattern.spec.whatwg.org/#canonicalize-a-port
+            Encoding::Port => {
+                if !value.chars().all(|chr| chr.is_ascii_digit()) {
+                    return Err(error("port"));
+                }
+                value
+                    .parse::<u16>()
+                    .map(|port| port.to_string())
+                    .map_err(|_| error("port"))
+            }
+            // https://urlpattern.spec.whatwg.org/#canonicalize-a-pathname
+            Encoding::Pathname => {
+                // Let leading slash be true if the first code point in value is U+002F (/) and otherwise false.
+                let leading_slash = value.starts_with('/');
+
+                // Let modified value be "/-" if leading slash is false and otherwise the empty string.
+                let modified = if leading_slash {
+                    value.to_string()
+                } else {
+                    format!("/-{value}")
+                };
+
+                let mut url = dummy_url("http://dummy.invalid/");
+                url.set_path(&modified);
+
+                // If leading slash is false, then set result to the code point substring from 2 to the end of the string within result.
+                let path = url.path();
+                Ok(if leading_slash { path } else { &path[2..] }.into())
+            }
+            // https://urlpattern.spec.whatwg.org/#canonicalize-an-opaque-pathname
+            Encoding::OpaquePathname => {
+                let mut url = dummy_url("fake:dummy");
+                url.set_path(value);
+                Ok(url.path().into())
+            }
+            // https://urlpattern.spec.whatwg.org/#canonicalize-a-search
+            Encoding::Search => {
+                let mut url = dummy_url("fake://dummy.invalid/");
+                url.set_query(Some(value));
+                Ok(url.query().unwrap_or_default().into())
+            }
+            // https://urlpattern.spec.whatwg.org/#canonicalize-a-hash
+            Encoding::Hash => {
+                let mut url = dummy_url("fake://dummy.invalid/");
+                url.set_fragment(Some(value));
+                Ok(url.fragment().unwrap_or_default().into())
+            }
+        }
+    }
+}
+
+fn components(url: &Url) -> (String, UrlComponents<'_>) {
+    // `Url::port` is already `None` for the default port of the scheme.
+    let port = url.port().map(|port| port.to_string()).unwrap_or_default();
+    let components = UrlComponents {
+        protocol: url.scheme(),
+        username: url.username(),
+        password: url.password().unwrap_or_default(),
+        hostname: url.host_str().unwrap_or_default(),
+        port: "",
+        pathname: url.path(),
+        search: url.query().unwrap_or_default(),
+        hash: url.fragment().unwrap_or_default(),
+    };
+    (port, components)
+}
+
+impl From<&Url> for UrlPatternInit {
+    fn from(url: &Url) -> UrlPatternInit {
+        let (port, components) = components(url);
+        UrlPatternInit {
+            protocol: Some(components.protocol.into()),
+            username: Some(components.username.into()),
+            password: Some(components.password.into()),
+            hostname: Some(components.hostname.into()),
+            port: Some(port),
+            pathname: Some(components.pathname.into()),
+            search: Some(components.search.into()),
+            hash: Some(components.hash.into()),
+            base_url: None,
+        }
+    }
+}
+
+impl UrlPattern {
+    /// Matches the components of a [`url::Url`].
+    ///
+    /// The `Url` is already canonicalized by the URL parser, so its components
+    /// are matched as they are.
+    pub fn exec_parsed_url(&self, url: &Url) -> Option<UrlPatternResult> {
+        let (port, components) = components(url);
+        self.exec_components(&UrlComponents {
+            port: &port,
+            ..components
+        })
+    }
+
+    /// Returns whether a [`url::Url`] matches.
+    pub fn test_parsed_url(&self, url: &Url) -> bool {
+        self.exec_parsed_url(url).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::UrlPatternOptions;
+
+    use super::*;
+
+    fn parse(input: &str) -> UrlPattern {
+        UrlPattern::parse(input, None, &UrlPatternOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn parsed_url() {
+        let pattern = parse("https://:user@*.example.com/files/:name.txt");
+        let url = Url::parse("HTTPS://alice@WWW.Example.com:443/files/a%20b.txt").unwrap();
+        let result = pattern.exec_parsed_url(&url).unwrap();
+        assert_eq!(result.username.groups["user"].as_deref(), Some("alice"));
+        assert_eq!(result.pathname.groups["name"].as_deref(), Some("a%20b"));
+        assert!(!pattern
+            .test_parsed_url(&Url::parse("https://www.example.com:8443/files/a.txt").unwrap()));
+    }
+
+    #[test]
+    fn canonicalize() {
+        let pattern = parse("HTTPS://EXAMPLE.com/caf\u{e9}/a b?q=a b#x y");
+        assert_eq!(pattern.protocol().pattern_string(), "https");
+        assert_eq!(pattern.hostname().pattern_string(), "example.com");
+        assert_eq!(pattern.pathname().pattern_string(), "/caf%C3%A9/a%20b");
+        assert_eq!(pattern.search().pattern_string(), "q=a%20b");
+        assert_eq!(pattern.hash().pattern_string(), "x%20y");
+        assert!(
+            pattern.test_parsed_url(&Url::parse("https://example.com/café/a b?q=a b#x y").unwrap())
+        );
+        assert!(pattern.test_url("https://example.com/café/a b?q=a b#x y"));
+
+        let pattern = UrlPattern::from_init(
+            &UrlPatternInit {
+                protocol: Some("https".into()),
+                hostname: Some(r"[\:\:AB]".into()),
+                ..Default::default()
+            },
+            &UrlPatternOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(pattern.hostname().pattern_string(), r"[\:\:ab]");
+        assert!(pattern.test_parsed_url(&Url::parse("https://[::ab]:8080/").unwrap()));
+
+        assert!(matches!(
+            UrlPattern::parse(
+                "https://example.com:99999/",
+                None,
+                &UrlPatternOptions::default()
+            ),
+            Err(UrlPatternError::Canonicalize {
+                component: "port",
+                ..
+            })
+        ));
+    }
+}
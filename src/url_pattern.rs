@@ -0,0 +1,861 @@
+// SPDX-License-Identifier: MIT
+
+use thiserror::Error;
+
+use crate::constructor_parser::parse_constructor_string;
+use crate::{
+    error_position, escape_pattern_string, parse_pattern, Component, ComponentResult, Explanation,
+    Options, ParseError, Part,
+};
+
+/// <https://url.spec.whatwg.org/#special-scheme> and their default ports.
+pub(crate) const SPECIAL_SCHEMES: [(&str, Option<&str>); 6] = [
+    ("ftp", Some("21")),
+    ("file", None),
+    ("http", Some("80")),
+    ("https", Some("443")),
+    ("ws", Some("80")),
+    ("wss", Some("443")),
+];
+
+/// The pattern or input strings of every URL component.
+///
+/// <https://urlpattern.spec.whatwg.org/#dictdef-urlpatterninit>
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct UrlPatternInit {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub protocol: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub username: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub password: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub hostname: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub port: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub pathname: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub search: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub hash: Option<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "baseURL", skip_serializing_if = "Option::is_none")
+    )]
+    pub base_url: Option<String>,
+}
+
+/// <https://urlpattern.spec.whatwg.org/#dictdef-urlpatternoptions>
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UrlPatternOptions {
+    pub ignore_case: bool,
+}
+
+#[derive(Error, Debug)]
+pub enum UrlPatternError {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error("a relative pattern needs a base URL")]
+    MissingBaseUrl,
+    #[error("invalid base URL {0:?}")]
+    InvalidBaseUrl(String),
+    #[error("invalid {component} {value:?}")]
+    Canonicalize {
+        component: &'static str,
+        value: String,
+    },
+    /// The pattern of a component is invalid. `position` is the byte offset
+    /// in `pattern` at which parsing it failed, if the error comes from
+    /// parsing it.
+    #[error("invalid {component} pattern {pattern:?}: {error}")]
+    Component {
+        component: &'static str,
+        pattern: String,
+        position: Option<usize>,
+        #[source]
+        error: ParseError,
+    },
+}
+
+/// A pattern for matching whole URLs, made of a [`Component`] for every part
+/// of a URL.
+///
+/// <https://urlpattern.spec.whatwg.org/#urlpattern>
+#[derive(Debug, Clone)]
+pub struct UrlPattern {
+    protocol: Component,
+    username: Component,
+    password: Component,
+    hostname: Component,
+    port: Component,
+    pathname: Component,
+    search: Component,
+    hash: Component,
+}
+
+/// <https://urlpattern.spec.whatwg.org/#dictdef-urlpatternresult>
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UrlPatternResult {
+    pub protocol: ComponentResult,
+    pub username: ComponentResult,
+    pub password: ComponentResult,
+    pub hostname: ComponentResult,
+    pub port: ComponentResult,
+    pub pathname: ComponentResult,
+    pub search: ComponentResult,
+    pub hash: ComponentResult,
+}
+
+/// The input strings of every component, borrowed from a parsed URL.
+pub(crate) struct UrlComponents<'a> {
+    pub(crate) protocol: &'a str,
+    pub(crate) username: &'a str,
+    pub(crate) password: &'a str,
+    pub(crate) hostname: &'a str,
+    pub(crate) port: &'a str,
+    pub(crate) pathname: &'a str,
+    pub(crate) search: &'a str,
+    pub(crate) hash: &'a str,
+}
+
+/// The names of the components of a URL, in the order of [`UrlPattern::components`].
+const COMPONENT_NAMES: [&str; 8] = [
+    "protocol", "username", "password", "hostname", "port", "pathname", "search", "hash",
+];
+
+/// How the fixed text of a component is canonicalized.
+///
+/// Without the `url` feature there is no URL parser to canonicalize with, so
+/// the fixed text is used as written.
+///
+/// <https://urlpattern.spec.whatwg.org/#canon-encoding-callbacks>
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Encoding {
+    Protocol,
+    Username,
+    Password,
+    Hostname,
+    OpaqueHostname,
+    Ipv6Hostname,
+    Port,
+    Pathname,
+    OpaquePathname,
+    Search,
+    Hash,
+}
+
+impl Encoding {
+    /// The name of the component this encoding is for.
+    pub(crate) fn component(self) -> &'static str {
+        match self {
+            Encoding::Protocol => "protocol",
+            Encoding::Username => "username",
+            Encoding::Password => "password",
+            Encoding::Hostname | Encoding::OpaqueHostname | Encoding::Ipv6Hostname => "hostname",
+            Encoding::Port => "port",
+            Encoding::Pathname | Encoding::OpaquePathname => "pathname",
+            Encoding::Search => "search",
+            Encoding::Hash => "hash",
+        }
+    }
+}
+
+#[cfg(not(feature = "url"))]
+impl Encoding {
+    pub(crate) fn canonicalize(self, value: &str) -> Result<String, UrlPatternError> {
+        Ok(value.into())
+    }
+}
+
+/// <https://urlpattern.spec.whatwg.org/#compile-a-component>
+fn compile_component(
+    input: &str,
+    encoding: Encoding,
+    options: &Options,
+) -> Result<Component, UrlPatternError> {
+    let error = |error| UrlPatternError::Component {
+        component: encoding.component(),
+        pattern: input.into(),
+        position: error_position(input, options),
+        error,
+    };
+
+    // Let part list be the result of running parse a pattern string given input, options, and encoding callback.
+    let mut parts = parse_pattern(input, options).map_err(error)?;
+    for part in &mut parts {
+        match part {
+            Part::FixedText { value, .. } => *value = encoding.canonicalize(value)?,
+            Part::SegmentWildcard { prefix, suffix, .. }
+            | Part::FullWildcard { prefix, suffix, .. }
+            | Part::RegExp { prefix, suffix, .. } => {
+                *prefix = encoding.canonicalize(prefix)?;
+                *suffix = encoding.canonicalize(suffix)?;
+            }
+        }
+    }
+    Component::from_parts(parts, options).map_err(error)
+}
+
+/// <https://urlpattern.spec.whatwg.org/#is-an-ipv6-address>
+fn is_ipv6_address(input: &str) -> bool {
+    input.starts_with('[') || input.starts_with("{[") || input.starts_with("\\[")
+}
+
+/// Returns the empty string if `port` is the default port of `scheme`, like
+/// the URL parser does.
+pub(crate) fn non_default_port<'a>(scheme: &str, port: &'a str) -> &'a str {
+    if SPECIAL_SCHEMES
+        .iter()
+        .any(|&(special, default_port)| special == scheme && default_port == Some(port))
+    {
+        ""
+    } else {
+        port
+    }
+}
+
+/// Which kind of strings a [`UrlPatternInit`] is processed as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InitType {
+    Pattern,
+    Url,
+}
+
+impl UrlPatternInit {
+    /// Splits a serialized URL into its components.
+    ///
+    /// With the `url` feature the URL is parsed and canonicalized by the URL
+    /// parser. Otherwise it isn't canonicalized, so it should already be in the
+    /// form the URL parser serializes to.
+    pub fn from_url(input: &str) -> Option<UrlPatternInit> {
+        #[cfg(feature = "url")]
+        return ::url::Url::parse(input)
+            .ok()
+            .map(|url| UrlPatternInit::from(&url));
+
+        #[cfg(not(feature = "url"))]
+        UrlPatternInit::split_url(input)
+    }
+
+    #[cfg(not(feature = "url"))]
+    fn split_url(input: &str) -> Option<UrlPatternInit> {
+        // https://url.spec.whatwg.org/#scheme-state
+        let (scheme, mut rest) = input.split_once(':')?;
+        if !scheme.starts_with(|chr: char| chr.is_ascii_alphabetic())
+            || !scheme
+                .chars()
+                .all(|chr| chr.is_ascii_alphanumeric() || "+-.".contains(chr))
+        {
+            return None;
+        }
+        let protocol = scheme.to_ascii_lowercase();
+        let special = SPECIAL_SCHEMES
+            .iter()
+            .any(|(scheme, _)| *scheme == protocol);
+
+        let mut init = UrlPatternInit {
+            protocol: Some(protocol),
+            username: Some(String::new()),
+            password: Some(String::new()),
+            hostname: Some(String::new()),
+            port: Some(String::new()),
+            ..Default::default()
+        };
+
+        if let Some(after_slashes) = rest.strip_prefix("//") {
+            let end = after_slashes
+                .find(['/', '?', '#'])
+                .unwrap_or(after_slashes.len());
+            let mut authority = &after_slashes[..end];
+            rest = &after_slashes[end..];
+
+            if let Some((userinfo, host)) = authority.rsplit_once('@') {
+                let (username, password) = userinfo.split_once(':').unwrap_or((userinfo, ""));
+                init.username = Some(username.into());
+                init.password = Some(password.into());
+                authority = host;
+            }
+
+            // The port follows the last `:` that isn't part of an IPv6 address.
+            let (hostname, port) = match authority.rfind(':') {
+                Some(index) if !authority[index..].contains(']') => {
+                    (&authority[..index], &authority[index + 1..])
+                }
+                _ => (authority, ""),
+            };
+            init.hostname = Some(hostname.to_ascii_lowercase());
+            init.port = Some(port.into());
+        }
+
+        let (rest, hash) = rest.split_once('#').unwrap_or((rest, ""));
+        let (pathname, search) = rest.split_once('?').unwrap_or((rest, ""));
+        init.pathname = Some(if special && pathname.is_empty() {
+            "/".into()
+        } else {
+            pathname.into()
+        });
+        init.search = Some(search.into());
+        init.hash = Some(hash.into());
+        Some(init)
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#process-a-urlpatterninit>
+    fn process(&self, init_type: InitType) -> Result<UrlPatternInit, UrlPatternError> {
+        // Let result be the result of creating a new URLPatternInit.
+        let mut result = match init_type {
+            InitType::Pattern => UrlPatternInit::default(),
+            InitType::Url => UrlPatternInit {
+                protocol: Some(String::new()),
+                username: Some(String::new()),
+                password: Some(String::new()),
+                hostname: Some(String::new()),
+                port: Some(String::new()),
+                pathname: Some(String::new()),
+                search: Some(String::new()),
+                hash: Some(String::new()),
+                base_url: None,
+            },
+        };
+
+        // https://urlpattern.spec.whatwg.org/#process-a-base-url-string
+        let process_base_url_string = |input: &str| match init_type {
+            InitType::Pattern => escape_pattern_string(input),
+            InitType::Url => input.to_string(),
+        };
+
+        // If init["baseURL"] exists:
+        let mut base_url = None;
+        if let Some(base) = &self.base_url {
+            // Set baseURL to the result of running the basic URL parser on init["baseURL"].
+            let base = UrlPatternInit::from_url(base)
+                .ok_or_else(|| UrlPatternError::InvalidBaseUrl(base.clone()))?;
+            let component = |value: &Option<String>| {
+                Some(process_base_url_string(
+                    value.as_deref().unwrap_or_default(),
+                ))
+            };
+
+            // If init["protocol"] does not exist, then set result["protocol"] to the result of processing a base URL string given baseURL’s scheme and type.
+            if self.protocol.is_none() {
+                result.protocol = component(&base.protocol);
+            }
+
+            // If type is not "pattern" and init contains none of "protocol", "hostname", "port" and "username", then set result["username"] to the result of processing a base URL string given baseURL’s username and type.
+            if init_type != InitType::Pattern
+                && self.protocol.is_none()
+                && self.hostname.is_none()
+                && self.port.is_none()
+                && self.username.is_none()
+            {
+                result.username = component(&base.username);
+            }
+
+            // If type is not "pattern" and init contains none of "protocol", "hostname", "port", "username" and "password", then set result["password"] to the result of processing a base URL string given baseURL’s password and type.
+            if init_type != InitType::Pattern
+                && self.protocol.is_none()
+                && self.hostname.is_none()
+                && self.port.is_none()
+                && self.username.is_none()
+                && self.password.is_none()
+            {
+                result.password = component(&base.password);
+            }
+
+            // If init contains neither "protocol" nor "hostname", then: ..
+            if self.protocol.is_none() && self.hostname.is_none() {
+                result.hostname = component(&base.hostname);
+            }
+
+            // If init contains none of "protocol", "hostname", and "port", then: ..
+            if self.protocol.is_none() && self.hostname.is_none() && self.port.is_none() {
+                result.port = component(&base.port);
+            }
+
+            // If init contains none of "protocol", "hostname", "port", and "pathname", then set result["pathname"] to the result of processing a base URL string given the result of URL path serializing baseURL and type.
+            if self.protocol.is_none()
+                && self.hostname.is_none()
+                && self.port.is_none()
+                && self.pathname.is_none()
+            {
+                result.pathname = component(&base.pathname);
+            }
+
+            // If init contains none of "protocol", "hostname", "port", "pathname", and "search", then: ..
+            if self.protocol.is_none()
+                && self.hostname.is_none()
+                && self.port.is_none()
+                && self.pathname.is_none()
+                && self.search.is_none()
+            {
+                result.search = component(&base.search);
+            }
+
+            // If init contains none of "protocol", "hostname", "port", "pathname", "search", and "hash", then: ..
+            if self.protocol.is_none()
+                && self.hostname.is_none()
+                && self.port.is_none()
+                && self.pathname.is_none()
+                && self.search.is_none()
+                && self.hash.is_none()
+            {
+                result.hash = component(&base.hash);
+            }
+
+            base_url = Some(base);
+        }
+
+        // If init["protocol"] exists, then set result["protocol"] to the result of process protocol for init given init["protocol"] and type.
+        if let Some(protocol) = &self.protocol {
+            let protocol = protocol.strip_suffix(':').unwrap_or(protocol);
+            result.protocol = Some(match init_type {
+                InitType::Pattern => protocol.into(),
+                InitType::Url => protocol.to_ascii_lowercase(),
+            });
+        }
+        if let Some(username) = &self.username {
+            result.username = Some(username.clone());
+        }
+        if let Some(password) = &self.password {
+            result.password = Some(password.clone());
+        }
+        if let Some(hostname) = &self.hostname {
+            result.hostname = Some(match init_type {
+                InitType::Pattern => hostname.clone(),
+                InitType::Url => hostname.to_ascii_lowercase(),
+            });
+        }
+        if let Some(port) = &self.port {
+            result.port = Some(port.clone());
+        }
+
+        // If init["pathname"] exists:
+        if let Some(pathname) = &self.pathname {
+            let mut pathname = pathname.clone();
+
+            // If baseURL is not null, baseURL does not have an opaque path, and the result of running is an absolute pathname given result["pathname"] and type is false, then:
+            if let Some(base) = &base_url {
+                let base_pathname = base.pathname.as_deref().unwrap_or_default();
+                let is_absolute = pathname.starts_with('/')
+                    || (init_type == InitType::Pattern
+                        && (pathname.starts_with("\\/") || pathname.starts_with("{/")));
+                if base_pathname.starts_with('/') && !is_absolute {
+                    // Let baseURLPath be the result of running process a base URL string given the result of URL path serializing baseURL and type.
+                    let base_url_path = process_base_url_string(base_pathname);
+
+                    // If slash index is not null: ..
+                    if let Some(slash_index) = base_url_path.rfind('/') {
+                        pathname = format!("{}{pathname}", &base_url_path[..=slash_index]);
+                    }
+                }
+            }
+
+            result.pathname = Some(pathname);
+        }
+
+        if let Some(search) = &self.search {
+            result.search = Some(search.strip_prefix('?').unwrap_or(search).into());
+        }
+        if let Some(hash) = &self.hash {
+            result.hash = Some(hash.strip_prefix('#').unwrap_or(hash).into());
+        }
+
+        Ok(result)
+    }
+}
+
+impl UrlPattern {
+    /// Parses a constructor string like `https://*.example.com/users/:id`.
+    ///
+    /// <https://urlpattern.spec.whatwg.org/#dom-urlpattern-urlpattern>
+    pub fn parse(
+        input: &str,
+        base_url: Option<&str>,
+        options: &UrlPatternOptions,
+    ) -> Result<UrlPattern, UrlPatternError> {
+        // Let init be the result of running parse a constructor string given input.
+        let mut init = parse_constructor_string(input)?;
+
+        // If baseURL is null and init["protocol"] does not exist, then throw a TypeError.
+        if base_url.is_none() && init.protocol.is_none() {
+            return Err(UrlPatternError::MissingBaseUrl);
+        }
+
+        // If baseURL is not null, set init["baseURL"] to baseURL.
+        init.base_url = base_url.map(String::from);
+
+        UrlPattern::from_init(&init, options)
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#url-pattern-create>
+    pub fn from_init(
+        init: &UrlPatternInit,
+        options: &UrlPatternOptions,
+    ) -> Result<UrlPattern, UrlPatternError> {
+        // Let processedInit be the result of process a URLPatternInit given init, "pattern", null, null, null, null, null, null, null, and null.
+        let mut processed = init.process(InitType::Pattern)?;
+
+        // For each componentName of « "protocol", "username", "password", "hostname", "port", "pathname", "search", "hash" »:
+        // If processedInit[componentName] does not exist, then set processedInit[componentName] to "*".
+        let wildcard = |component: Option<String>| component.unwrap_or_else(|| "*".into());
+        let protocol = wildcard(processed.protocol.take());
+
+        // If processedInit["protocol"] is a special scheme and processedInit["port"] is a string which represents its corresponding default port in radix-10 using ASCII digits then set processedInit["port"] to the empty string.
+        let port = wildcard(processed.port.take());
+        let port = non_default_port(&protocol, &port);
+
+        let default_options = Options {
+            ignore_case: options.ignore_case,
+            ..Options::default()
+        };
+        // Set urlPattern’s protocol component to the result of compiling a component given processedInit["protocol"], canonicalize a protocol, and default options.
+        let protocol = compile_component(&protocol, Encoding::Protocol, &default_options)?;
+
+        // If the result of running protocol component matches a special scheme given urlPattern’s protocol component is true, then set urlPattern’s pathname component to the result of compiling a component given processedInit["pathname"], canonicalize a pathname, and compile options.
+        let special = SPECIAL_SCHEMES
+            .iter()
+            .any(|(scheme, _)| protocol.test(scheme));
+        let (pathname_encoding, pathname_options) = if special {
+            (
+                Encoding::Pathname,
+                Options {
+                    ignore_case: options.ignore_case,
+                    ..Options::pathname()
+                },
+            )
+        } else {
+            (Encoding::OpaquePathname, default_options.clone())
+        };
+        let hostname_options = Options {
+            ignore_case: options.ignore_case,
+            ..Options::hostname()
+        };
+
+        // If the result running hostname pattern is an IPv6 address given processedInit["hostname"] is true, then set urlPattern’s hostname component to the result of compiling a component given processedInit["hostname"], canonicalize an IPv6 hostname, and hostname options.
+        let hostname = wildcard(processed.hostname);
+        let hostname_encoding = if is_ipv6_address(&hostname) {
+            Encoding::Ipv6Hostname
+        } else if special {
+            Encoding::Hostname
+        } else {
+            Encoding::OpaqueHostname
+        };
+
+        Ok(UrlPattern {
+            protocol,
+            username: compile_component(
+                &wildcard(processed.username),
+                Encoding::Username,
+                &default_options,
+            )?,
+            password: compile_component(
+                &wildcard(processed.password),
+                Encoding::Password,
+                &default_options,
+            )?,
+            hostname: compile_component(&hostname, hostname_encoding, &hostname_options)?,
+            port: compile_component(port, Encoding::Port, &default_options)?,
+            pathname: compile_component(
+                &wildcard(processed.pathname),
+                pathname_encoding,
+                &pathname_options,
+            )?,
+            search: compile_component(
+                &wildcard(processed.search),
+                Encoding::Search,
+                &default_options,
+            )?,
+            hash: compile_component(&wildcard(processed.hash), Encoding::Hash, &default_options)?,
+        })
+    }
+
+    pub fn protocol(&self) -> &Component {
+        &self.protocol
+    }
+
+    pub fn username(&self) -> &Component {
+        &self.username
+    }
+
+    pub fn password(&self) -> &Component {
+        &self.password
+    }
+
+    pub fn hostname(&self) -> &Component {
+        &self.hostname
+    }
+
+    pub fn port(&self) -> &Component {
+        &self.port
+    }
+
+    pub fn pathname(&self) -> &Component {
+        &self.pathname
+    }
+
+    pub fn search(&self) -> &Component {
+        &self.search
+    }
+
+    pub fn hash(&self) -> &Component {
+        &self.hash
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#url-pattern-has-regexp-groups>
+    pub fn has_regexp_groups(&self) -> bool {
+        self.components()
+            .iter()
+            .any(|component| component.has_regexp_groups())
+    }
+
+    /// The components in the order of [`COMPONENT_NAMES`].
+    pub(crate) fn components(&self) -> [&Component; 8] {
+        [
+            &self.protocol,
+            &self.username,
+            &self.password,
+            &self.hostname,
+            &self.port,
+            &self.pathname,
+            &self.search,
+            &self.hash,
+        ]
+    }
+
+    /// Builds a pattern from its components, in the order of
+    /// [`UrlPattern::components`].
+    pub(crate) fn from_components(components: [Component; 8]) -> UrlPattern {
+        let [protocol, username, password, hostname, port, pathname, search, hash] = components;
+        UrlPattern {
+            protocol,
+            username,
+            password,
+            hostname,
+            port,
+            pathname,
+            search,
+            hash,
+        }
+    }
+
+    /// The pattern strings of every component, in the form accepted by
+    /// [`UrlPattern::from_init`].
+    pub fn to_init(&self) -> UrlPatternInit {
+        let [protocol, username, password, hostname, port, pathname, search, hash] = self
+            .components()
+            .map(|component| Some(component.pattern_string().to_string()));
+        UrlPatternInit {
+            protocol,
+            username,
+            password,
+            hostname,
+            port,
+            pathname,
+            search,
+            hash,
+            base_url: None,
+        }
+    }
+
+    /// Whether the components were compiled to match case-insensitively.
+    pub fn ignore_case(&self) -> bool {
+        self.protocol.options().ignore_case
+    }
+
+    /// Matches the components of a URL, given as [`UrlPatternInit`] strings.
+    ///
+    /// <https://urlpattern.spec.whatwg.org/#url-pattern-match>
+    pub fn exec(&self, input: &UrlPatternInit) -> Option<UrlPatternResult> {
+        // Set applyResult to the result of process a URLPatternInit given input, "url", protocol, username, password, hostname, port, pathname, search, and hash.
+        // If this throws an exception, catch it, and return null.
+        let input = input.process(InitType::Url).ok()?;
+        fn value(value: &Option<String>) -> &str {
+            value.as_deref().unwrap_or_default()
+        }
+
+        self.exec_components(&UrlComponents {
+            protocol: value(&input.protocol),
+            username: value(&input.username),
+            password: value(&input.password),
+            hostname: value(&input.hostname),
+            port: value(&input.port),
+            pathname: value(&input.pathname),
+            search: value(&input.search),
+            hash: value(&input.hash),
+        })
+    }
+
+    /// Matches every component against the canonicalized input strings.
+    pub(crate) fn exec_components(&self, input: &UrlComponents<'_>) -> Option<UrlPatternResult> {
+        Some(UrlPatternResult {
+            protocol: self.protocol.exec(input.protocol)?,
+            username: self.username.exec(input.username)?,
+            password: self.password.exec(input.password)?,
+            hostname: self.hostname.exec(input.hostname)?,
+            port: self.port.exec(input.port)?,
+            pathname: self.pathname.exec(input.pathname)?,
+            search: self.search.exec(input.search)?,
+            hash: self.hash.exec(input.hash)?,
+        })
+    }
+
+    /// Explains which component, and which part of it, rejected `input`, or
+    /// returns `None` if it matches.
+    ///
+    /// The components are checked in order, so the first one that rejects
+    /// the processed input is explained. Fails if `input` can't be processed,
+    /// for example because it's relative without a base URL.
+    pub fn explain(
+        &self,
+        input: &UrlPatternInit,
+    ) -> Result<Option<(&'static str, Explanation)>, UrlPatternError> {
+        let input = input.process(InitType::Url)?;
+        let values = [
+            input.protocol,
+            input.username,
+            input.password,
+            input.hostname,
+            input.port,
+            input.pathname,
+            input.search,
+            input.hash,
+        ];
+        let names = COMPONENT_NAMES.iter();
+        for ((name, component), value) in names.zip(self.components()).zip(values) {
+            if let Some(explanation) = component.explain(value.as_deref().unwrap_or_default()) {
+                return Ok(Some((name, explanation)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Matches a serialized URL, split with [`UrlPatternInit::from_url`].
+    pub fn exec_url(&self, url: &str) -> Option<UrlPatternResult> {
+        self.exec(&UrlPatternInit::from_url(url)?)
+    }
+
+    /// <https://urlpattern.spec.whatwg.org/#dom-urlpattern-test>
+    pub fn test(&self, input: &UrlPatternInit) -> bool {
+        self.exec(input).is_some()
+    }
+
+    /// Returns whether a serialized URL matches.
+    pub fn test_url(&self, url: &str) -> bool {
+        self.exec_url(url).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> UrlPattern {
+        UrlPattern::parse(input, None, &UrlPatternOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn constructor_string() {
+        let pattern = parse(r"https://:sub.example.com/users/:id\?tab=:tab#top");
+        let init = pattern.to_init();
+        assert_eq!(init.protocol.as_deref(), Some("https"));
+        assert_eq!(init.username.as_deref(), Some("*"));
+        assert_eq!(init.hostname.as_deref(), Some(":sub.example.com"));
+        assert_eq!(init.port.as_deref(), Some(""));
+        assert_eq!(init.pathname.as_deref(), Some("/users/:id"));
+        assert_eq!(init.search.as_deref(), Some("tab=:tab"));
+        assert_eq!(init.hash.as_deref(), Some("top"));
+
+        let result = pattern
+            .exec_url("https://api.example.com/users/42?tab=files#top")
+            .unwrap();
+        assert_eq!(result.hostname.groups["sub"].as_deref(), Some("api"));
+        assert_eq!(result.pathname.groups["id"].as_deref(), Some("42"));
+        assert!(!pattern.test_url("https://api.example.com:8080/users/42?tab=files#top"));
+
+        let pattern = parse("http{s}?://*.example.com/*");
+        assert!(pattern.test_url("http://www.example.com/a/b"));
+        assert!(pattern.test_url("https://www.example.com/"));
+        assert!(!pattern.test_url("https://www.example.com:8080/"));
+
+        assert!(matches!(
+            UrlPattern::parse("/users/:id", None, &UrlPatternOptions::default()),
+            Err(UrlPatternError::MissingBaseUrl)
+        ));
+
+        let pattern = UrlPattern::parse(
+            ":id",
+            Some("https://example.com/users/list"),
+            &UrlPatternOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(pattern.pathname().pattern_string(), "/users/:id");
+        assert_eq!(pattern.hostname().pattern_string(), "example.com");
+    }
+
+    #[test]
+    fn init() {
+        let pattern = UrlPattern::from_init(
+            &UrlPatternInit {
+                pathname: Some("/static/*".into()),
+                base_url: Some("https://cdn.example.com".into()),
+                ..Default::default()
+            },
+            &UrlPatternOptions { ignore_case: true },
+        )
+        .unwrap();
+        assert!(pattern.test_url("https://CDN.example.com/STATIC/app.js"));
+        assert!(!pattern.test_url("http://cdn.example.com/static/app.js"));
+    }
+
+    #[test]
+    fn explain() {
+        let pattern = parse("https://:sub.example.com/users/:id(\\d+)");
+        let explain = |url| pattern.explain(&UrlPatternInit::from_url(url).unwrap());
+        assert_eq!(explain("https://api.example.com/users/42").unwrap(), None);
+
+        let (component, explanation) = explain("https://api.example.com/users/me")
+            .unwrap()
+            .unwrap();
+        assert_eq!(component, "pathname");
+        assert_eq!(explanation.matched, "/users");
+        assert_eq!(explanation.expected, r"/:id(\d+)");
+
+        // The first component that rejects the input is explained.
+        let (component, explanation) = explain("http://example.org/posts").unwrap().unwrap();
+        assert_eq!(component, "protocol");
+        assert_eq!(explanation.actual, "http");
+
+        let input = UrlPatternInit {
+            pathname: Some("/users/42".into()),
+            ..Default::default()
+        };
+        let (component, explanation) = pattern.explain(&input).unwrap().unwrap();
+        assert_eq!(component, "protocol");
+        assert_eq!(explanation.actual, "");
+    }
+
+    #[test]
+    fn component_error() {
+        let error = UrlPattern::parse(
+            "https://example.com/:id/:id",
+            None,
+            &UrlPatternOptions::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            UrlPatternError::Component {
+                component: "pathname",
+                position: Some(5),
+                error: ParseError::DuplicateName(_),
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+// SPDX-License-Identifier: MIT
+
+//! Runs the `url-pattern` binary and checks its output and exit status.
+
+#![cfg(feature = "regex")]
+
+use std::process::Command;
+
+/// Runs the binary with `args` and returns its exit code, stdout and stderr.
+fn run(args: &[&str]) -> (i32, String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_url-pattern"))
+        .args(args)
+        .output()
+        .unwrap();
+    (
+        output.status.code().unwrap(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn compile() {
+    let (code, stdout, _) = run(&["compile", r"/users/:id(\d+)/*"]);
+    assert_eq!(code, 0);
+    assert_eq!(
+        stdout,
+        "pattern: /users/:id(\\d+)/*\n\
+         regexp: ^\\/users(?:\\/(\\d+))(?:\\/(.*))$\n\
+         flags: u\n\
+         groups: id, 0\n"
+    );
+
+    let (code, stdout, _) = run(&["--dialect", "rust", "compile", "/:id"]);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("regexp: ^(?:/([^/]+?))$\n"), "{stdout}");
+
+    // Every group captures in POSIX ERE.
+    let (code, stdout, _) = run(&["--dialect", "posix-ere", "compile", "/:id/*"]);
+    assert_eq!(code, 0);
+    assert_eq!(
+        stdout,
+        "pattern: /:id/*\n\
+         regexp: ^(/([^/]+))(/(.*))$\n\
+         groups: id, 0\n\
+         group indices: 2, 4\n"
+    );
+}
+
+#[test]
+fn test() {
+    let (code, stdout, _) = run(&[
+        "test",
+        "https://:tenant.example.com/users/:id",
+        "https://acme.example.com/users/42",
+    ]);
+    assert_eq!(code, 0);
+    assert!(stdout.starts_with("match: true\n"), "{stdout}");
+    assert!(
+        stdout.contains("  hostname tenant = \"acme\"\n"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("  pathname id = \"42\"\n"), "{stdout}");
+
+    let (code, stdout, _) = run(&[
+        "test",
+        "https://example.com/users/:id",
+        "https://example.com/posts/42",
+    ]);
+    assert_eq!(code, 1);
+    assert_eq!(stdout, "match: false\n");
+
+    // The base URL applies to a relative pattern and a relative URL.
+    let (code, stdout, _) = run(&[
+        "test",
+        "/users/:id",
+        "/users/5?tab=files",
+        "--base-url",
+        "https://example.com",
+    ]);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("  pathname id = \"5\"\n"), "{stdout}");
+}
+
+#[test]
+fn explain() {
+    let (code, stdout, _) = run(&["explain", "https://example.com/users/:id"]);
+    assert_eq!(code, 0);
+    assert_eq!(
+        stdout,
+        "protocol 0: fixed text \"https\"\n\
+         hostname 0: fixed text \"example.com\"\n\
+         pathname 0: fixed text \"/users\"\n\
+         pathname 1: segment wildcard `id`, prefix \"/\"\n"
+    );
+
+    let (code, stdout, _) = run(&[
+        "explain",
+        "https://example.com/users/:id",
+        "https://example.com/posts/1",
+    ]);
+    assert_eq!(code, 1);
+    assert!(stdout.contains("match: false\n"), "{stdout}");
+    assert!(
+        stdout.contains("rejected by pathname 0: fixed text \"/users\"\n"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("actual: \"/posts/1\"\n"), "{stdout}");
+
+    let (code, stdout, _) = run(&[
+        "explain",
+        "https://example.com/users/:id",
+        "http://example.com/users/1",
+    ]);
+    assert_eq!(code, 1);
+    assert!(
+        stdout.contains("rejected by protocol 0: fixed text \"https\"\n"),
+        "{stdout}"
+    );
+
+    let (code, stdout, _) = run(&[
+        "explain",
+        "/users/:id",
+        "/users/1",
+        "--base-url",
+        "https://example.com",
+    ]);
+    assert_eq!(code, 0);
+    assert!(stdout.ends_with("match: true\n"), "{stdout}");
+}
+
+#[test]
+fn errors() {
+    // Usage errors and invalid patterns.
+    let (code, _, stderr) = run(&[]);
+    assert_eq!(code, 2);
+    assert!(stderr.starts_with("error: missing command\n"), "{stderr}");
+    let (code, _, _) = run(&["--dialect", "perl", "compile", "/"]);
+    assert_eq!(code, 2);
+    let (code, _, stderr) = run(&["compile", "/:id/:id"]);
+    assert_eq!(code, 2);
+    assert_eq!(stderr, "error: invalid pattern: duplicate name `id`\n");
+
+    // A URL that can't be parsed isn't a non-match.
+    let (code, stdout, stderr) = run(&["test", "https://example.com/*", "example.com/a"]);
+    assert_eq!(code, 3);
+    assert_eq!(stdout, "");
+    assert_eq!(stderr, "error: invalid URL \"example.com/a\"\n");
+    let (code, _, _) = run(&["test", "https://example.com/*", "/a"]);
+    assert_eq!(code, 3);
+}
@@ -0,0 +1,131 @@
+/* SPDX-License-Identifier: MIT */
+
+/* Tests of the C interface, compiled and run by tests/ffi.rs. */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "url_pattern.h"
+
+static int failures = 0;
+
+#define CHECK(condition)                                                       \
+    do {                                                                       \
+        if (!(condition)) {                                                    \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__,   \
+                    #condition);                                               \
+            failures++;                                                        \
+        }                                                                      \
+    } while (0)
+
+static int streq(const char *a, const char *b) {
+    return a != NULL && b != NULL && strcmp(a, b) == 0;
+}
+
+static void test_match(void) {
+    UrlPatternFfiError *error = NULL;
+    UrlPattern *pattern = url_pattern_new(
+        "https://:tenant.example.com/users/:id(\\d+){/:tab}?", NULL, false, &error);
+    CHECK(pattern != NULL);
+    CHECK(error == NULL);
+
+    CHECK(url_pattern_test(pattern, "https://acme.example.com/users/42", &error));
+    CHECK(!url_pattern_test(pattern, "https://acme.example.com/users/bob", &error));
+    CHECK(url_pattern_exec(pattern, "http://acme.example.com/users/42", &error) == NULL);
+    /* Not matching isn't an error. */
+    CHECK(error == NULL);
+
+    UrlPatternGroups *groups =
+        url_pattern_exec(pattern, "https://acme.example.com/users/42", NULL);
+    CHECK(groups != NULL);
+
+    /* The username and password default to wildcards. */
+    const char *component, *name, *value;
+    CHECK(url_pattern_groups_next(groups, &component, &name, &value));
+    CHECK(streq(component, "username"));
+    CHECK(streq(name, "0"));
+    CHECK(streq(value, ""));
+    CHECK(url_pattern_groups_next(groups, &component, &name, &value));
+    CHECK(streq(component, "password"));
+
+    CHECK(url_pattern_groups_next(groups, &component, &name, &value));
+    CHECK(streq(component, "hostname"));
+    CHECK(streq(name, "tenant"));
+    CHECK(streq(value, "acme"));
+
+    CHECK(url_pattern_groups_next(groups, &component, &name, &value));
+    CHECK(streq(component, "pathname"));
+    CHECK(streq(name, "id"));
+    CHECK(streq(value, "42"));
+
+    /* The optional group didn't match. */
+    CHECK(url_pattern_groups_next(groups, &component, &name, &value));
+    CHECK(streq(name, "tab"));
+    CHECK(value == NULL);
+
+    CHECK(url_pattern_groups_next(groups, &component, &name, &value));
+    CHECK(streq(component, "search"));
+    CHECK(url_pattern_groups_next(groups, &component, &name, &value));
+    CHECK(streq(component, "hash"));
+    CHECK(!url_pattern_groups_next(groups, &component, &name, &value));
+
+    url_pattern_groups_free(groups);
+    url_pattern_free(pattern);
+}
+
+static void test_errors(void) {
+    UrlPatternFfiError *error = NULL;
+    CHECK(url_pattern_new("https://example.com/:id/:id", NULL, false, &error) == NULL);
+    CHECK(error != NULL);
+    printf("error: %s\n", url_pattern_error_message(error));
+    CHECK(streq(url_pattern_error_component(error), "pathname"));
+    CHECK(streq(url_pattern_error_pattern(error), "/:id/:id"));
+    CHECK(url_pattern_error_position(error) == 5);
+    url_pattern_error_free(error);
+
+    error = NULL;
+    CHECK(url_pattern_new("https://example.com/files/(\\d+", NULL, false, &error) == NULL);
+    CHECK(streq(url_pattern_error_component(error), "pathname"));
+    CHECK(url_pattern_error_position(error) == 7);
+    url_pattern_error_free(error);
+
+    error = NULL;
+    CHECK(url_pattern_new("/users/:id", NULL, false, &error) == NULL);
+    printf("error: %s\n", url_pattern_error_message(error));
+    CHECK(url_pattern_error_component(error) == NULL);
+    CHECK(url_pattern_error_position(error) == -1);
+    url_pattern_error_free(error);
+
+    /* Patterns relative to a base URL, and failing without an error out-parameter. */
+    UrlPattern *pattern =
+        url_pattern_new("/users/:id", "https://example.com", false, NULL);
+    CHECK(url_pattern_test(pattern, "https://example.com/users/42", NULL));
+    CHECK(url_pattern_new("\xff", NULL, false, NULL) == NULL);
+
+    /* Invalid arguments when matching. */
+    error = NULL;
+    CHECK(!url_pattern_test(pattern, NULL, &error));
+    CHECK(streq(url_pattern_error_message(error), "the URL is NULL"));
+    url_pattern_error_free(error);
+    error = NULL;
+    CHECK(url_pattern_exec(pattern, "https://example.com/\xff", &error) == NULL);
+    CHECK(error != NULL);
+    url_pattern_error_free(error);
+    error = NULL;
+    CHECK(!url_pattern_test(NULL, "https://example.com/users/42", &error));
+    CHECK(streq(url_pattern_error_message(error), "the pattern is NULL"));
+    url_pattern_error_free(error);
+    url_pattern_free(pattern);
+}
+
+int main(void) {
+    test_match();
+    test_errors();
+    if (failures != 0) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return EXIT_FAILURE;
+    }
+    printf("all C tests passed\n");
+    return EXIT_SUCCESS;
+}
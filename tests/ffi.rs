@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: MIT
+
+//! Generates `include/url_pattern.h` from `src/ffi.rs` and runs the C tests
+//! in `tests/ffi.c` against the shared library.
+//!
+//! Neither needs the `ffi` feature in the tests themselves: the header is
+//! generated from the source, and the shared library is built with the
+//! feature in a separate target directory. So both run with a plain
+//! `cargo test`.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Maps a Rust type from `src/ffi.rs` to C.
+fn c_type(ty: &str) -> String {
+    if let Some(pointee) = ty.strip_prefix("*const ") {
+        let pointee = c_type(pointee);
+        if pointee.ends_with('*') {
+            format!("{pointee} const *")
+        } else {
+            format!("const {pointee} *")
+        }
+    } else if let Some(pointee) = ty.strip_prefix("*mut ") {
+        let pointee = c_type(pointee);
+        if pointee.ends_with('*') {
+            format!("{pointee}*")
+        } else {
+            format!("{pointee} *")
+        }
+    } else {
+        match ty {
+            "bool" => "bool".into(),
+            "isize" => "ptrdiff_t".into(),
+            "c_char" => "char".into(),
+            _ => ty.into(),
+        }
+    }
+}
+
+/// `ty` followed by `name`, without a space after a `*`.
+fn declarator(ty: &str, name: &str) -> String {
+    if ty.ends_with('*') {
+        format!("{ty}{name}")
+    } else {
+        format!("{ty} {name}")
+    }
+}
+
+/// The opaque struct types a Rust type refers to.
+fn opaque_type(ty: &str) -> Option<&str> {
+    let base = ty.trim_start_matches("*const ").trim_start_matches("*mut ");
+    if base.starts_with('*') {
+        return opaque_type(base);
+    }
+    (!matches!(base, "bool" | "isize" | "c_char")).then_some(base)
+}
+
+fn generate_header(source: &str) -> String {
+    let mut types: Vec<String> = vec![];
+    let mut functions = String::new();
+    let mut docs: Vec<&str> = vec![];
+
+    let mut lines = source.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if let Some(doc) = line.strip_prefix("///") {
+            docs.push(doc.strip_prefix(' ').unwrap_or(doc));
+            continue;
+        }
+        if line == "#[no_mangle]" {
+            continue;
+        }
+        if !line.starts_with("pub unsafe extern \"C\" fn ") {
+            docs.clear();
+            continue;
+        }
+
+        let mut signature = line.to_string();
+        while !signature.ends_with('{') {
+            signature.push_str(lines.next().expect("end of signature").trim());
+        }
+        let signature = signature
+            .trim_start_matches("pub unsafe extern \"C\" fn ")
+            .trim_end_matches('{')
+            .trim();
+
+        let (name, rest) = signature.split_once('(').expect("arguments");
+        let (arguments, ret) = rest.rsplit_once(')').expect("end of arguments");
+        let ret = match ret.trim().strip_prefix("->") {
+            Some(ret) => ret.trim(),
+            None => "void",
+        };
+
+        let arguments: Vec<String> = arguments
+            .split(',')
+            .map(str::trim)
+            .filter(|argument| !argument.is_empty())
+            .map(|argument| {
+                let (name, ty) = argument.split_once(':').expect("typed argument");
+                let ty = ty.trim();
+                types.extend(opaque_type(ty).map(String::from));
+                declarator(&c_type(ty), name.trim())
+            })
+            .collect();
+        if ret != "void" {
+            types.extend(opaque_type(ret).map(String::from));
+        }
+
+        // Line comments, as docs can contain `/*` and `*/`.
+        functions.push('\n');
+        for doc in docs.drain(..) {
+            functions.push_str(&format!(
+                "//{}{doc}\n",
+                if doc.is_empty() { "" } else { " " }
+            ));
+        }
+        functions.push_str(&format!(
+            "{}({});\n",
+            declarator(&c_type(ret), name),
+            arguments.join(", ")
+        ));
+    }
+
+    let mut header = String::from(
+        "/* SPDX-License-Identifier: MIT */\n\
+         /* Generated from src/ffi.rs by tests/ffi.rs, don't edit. */\n\
+         \n\
+         #ifndef URL_PATTERN_H\n\
+         #define URL_PATTERN_H\n\
+         \n\
+         #include <stdbool.h>\n\
+         #include <stddef.h>\n\
+         \n\
+         #ifdef __cplusplus\n\
+         extern \"C\" {\n\
+         #endif\n\
+         \n",
+    );
+    let mut seen = vec![];
+    for ty in types {
+        if !seen.contains(&ty) {
+            header.push_str(&format!("typedef struct {ty} {ty};\n"));
+            seen.push(ty);
+        }
+    }
+    header.push_str(&functions);
+    header.push_str(
+        "\n\
+         #ifdef __cplusplus\n\
+         }\n\
+         #endif\n\
+         \n\
+         #endif /* URL_PATTERN_H */\n",
+    );
+    header
+}
+
+#[test]
+fn header() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let source = fs::read_to_string(root.join("src/ffi.rs")).unwrap();
+    let header = generate_header(&source);
+
+    let path = root.join("include/url_pattern.h");
+    if std::env::var_os("URL_PATTERN_UPDATE_HEADER").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &header).unwrap();
+    }
+    assert!(
+        fs::read_to_string(&path).is_ok_and(|existing| existing == header),
+        "include/url_pattern.h is out of date, run the test with URL_PATTERN_UPDATE_HEADER=1"
+    );
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn c_tests() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let tmp = Path::new(env!("CARGO_TARGET_TMPDIR"));
+
+    // Build the shared library in a separate target directory, so it doesn't
+    // clash with the build of the tests.
+    let target_dir = tmp.join("ffi-target");
+    let status = Command::new(env!("CARGO"))
+        .args([
+            "rustc",
+            "--lib",
+            "--features",
+            "ffi",
+            "--crate-type",
+            "cdylib",
+        ])
+        .arg("--manifest-path")
+        .arg(root.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "building the shared library failed");
+    let library_dir = target_dir.join("debug");
+
+    let output = tmp.join("ffi");
+    let status = Command::new(std::env::var("CC").unwrap_or_else(|_| "cc".into()))
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror"])
+        .arg("-I")
+        .arg(root.join("include"))
+        .arg(root.join("tests/ffi.c"))
+        .arg("-o")
+        .arg(&output)
+        .arg("-L")
+        .arg(&library_dir)
+        .arg(format!("-Wl,-rpath,{}", library_dir.display()))
+        .arg("-lurl_pattern")
+        .status()
+        .expect("a C compiler");
+    assert!(status.success(), "compiling tests/ffi.c failed");
+
+    // Cargo points `LD_LIBRARY_PATH` at its own build directories, which
+    // would take precedence over the rpath.
+    let result = Command::new(&output)
+        .env_remove("LD_LIBRARY_PATH")
+        .output()
+        .unwrap();
+    print!("{}", String::from_utf8_lossy(&result.stdout));
+    assert!(
+        result.status.success(),
+        "{}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+}
@@ -0,0 +1,420 @@
+// SPDX-License-Identifier: MIT
+
+//! Runs the URL Pattern cases of the web-platform-tests, vendored in
+//! `tests/wpt/urlpatterntestdata.json`.
+//!
+//! Every case is checked like `urlpattern.any.js` does: the construction
+//! error, the pattern string of every component, and the results of `test`
+//! and `exec`. The result of every case is written to
+//! `target/tmp/wpt-report.txt`.
+//!
+//! Not every case passes yet. The cases that are known to fail are listed in
+//! `tests/wpt/expected-failures.txt`, or `expected-failures-url.txt` with the
+//! `url` feature. The test fails if any other case fails, or if a listed case
+//! passes. Run it with `URL_PATTERN_UPDATE_WPT=1` to rewrite the list.
+
+#![cfg(feature = "regex")]
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+use url_pattern::{
+    ComponentResult, UrlPattern, UrlPatternInit, UrlPatternOptions, UrlPatternResult,
+};
+
+const COMPONENTS: [&str; 8] = [
+    "protocol", "username", "password", "hostname", "port", "pathname", "search", "hash",
+];
+
+/// The components of the base URLs in the data, like the getters of `URL`
+/// return them without their separators: protocol, hostname, port, pathname,
+/// search and hash.
+///
+/// They are written out instead of being split by the crate, so the pattern
+/// strings the crate derives from a base URL are checked against them.
+const BASE_URLS: [(&str, [&str; 6]); 10] = [
+    (
+        "https://example.com",
+        ["https", "example.com", "", "/", "", ""],
+    ),
+    (
+        "https://example.com/?q=*&v=?&hmm={}&umm=()",
+        ["https", "example.com", "", "/", "q=*&v=?&hmm={}&umm=()", ""],
+    ),
+    (
+        "https://example.com/a/+/b",
+        ["https", "example.com", "", "/a/+/b", "", ""],
+    ),
+    (
+        "https://example.com/foo",
+        ["https", "example.com", "", "/foo", "", ""],
+    ),
+    (
+        "https://example.com/foo#baz",
+        ["https", "example.com", "", "/foo", "", "baz"],
+    ),
+    (
+        "https://example.com/foo/",
+        ["https", "example.com", "", "/foo/", "", ""],
+    ),
+    (
+        "https://example.com/foo?bar",
+        ["https", "example.com", "", "/foo", "bar", ""],
+    ),
+    (
+        "https://example.com:8080",
+        ["https", "example.com", "8080", "/", "", ""],
+    ),
+    (
+        "https://example.com?otherquery#otherhash",
+        ["https", "example.com", "", "/", "otherquery", "otherhash"],
+    ),
+    (
+        "https://example.com?query#hash",
+        ["https", "example.com", "", "/", "query", "hash"],
+    ),
+];
+
+/// The value of `component` of a base URL from [`BASE_URLS`].
+fn base_url_component(base_url: &str, component: &str) -> &'static str {
+    let (_, components) = BASE_URLS
+        .iter()
+        .find(|(url, _)| *url == base_url)
+        .unwrap_or_else(|| panic!("{base_url:?} is missing from BASE_URLS"));
+    let index = ["protocol", "hostname", "port", "pathname", "search", "hash"]
+        .iter()
+        .position(|&c| c == component)
+        .expect("a component of a base URL");
+    components[index]
+}
+
+fn init_field<'a>(init: &'a UrlPatternInit, component: &str) -> &'a Option<String> {
+    match component {
+        "protocol" => &init.protocol,
+        "username" => &init.username,
+        "password" => &init.password,
+        "hostname" => &init.hostname,
+        "port" => &init.port,
+        "pathname" => &init.pathname,
+        "search" => &init.search,
+        "hash" => &init.hash,
+        _ => unreachable!(),
+    }
+}
+
+fn pattern_string<'a>(pattern: &'a UrlPattern, component: &str) -> &'a str {
+    match component {
+        "protocol" => pattern.protocol(),
+        "username" => pattern.username(),
+        "password" => pattern.password(),
+        "hostname" => pattern.hostname(),
+        "port" => pattern.port(),
+        "pathname" => pattern.pathname(),
+        "search" => pattern.search(),
+        "hash" => pattern.hash(),
+        _ => unreachable!(),
+    }
+    .pattern_string()
+}
+
+fn component_result<'a>(result: &'a UrlPatternResult, component: &str) -> &'a ComponentResult {
+    match component {
+        "protocol" => &result.protocol,
+        "username" => &result.username,
+        "password" => &result.password,
+        "hostname" => &result.hostname,
+        "port" => &result.port,
+        "pathname" => &result.pathname,
+        "search" => &result.search,
+        "hash" => &result.hash,
+        _ => unreachable!(),
+    }
+}
+
+fn parse_init(value: &Value) -> UrlPatternInit {
+    let field = |name: &str| value.get(name).and_then(Value::as_str).map(String::from);
+    UrlPatternInit {
+        protocol: field("protocol"),
+        username: field("username"),
+        password: field("password"),
+        hostname: field("hostname"),
+        port: field("port"),
+        pathname: field("pathname"),
+        search: field("search"),
+        hash: field("hash"),
+        base_url: field("baseURL"),
+    }
+}
+
+/// The arguments of the `URLPattern` constructor or of `exec`: a string or
+/// init, then an optional base URL and for the constructor optional options.
+struct Arguments<'a> {
+    input: Option<&'a Value>,
+    base_url: Option<&'a str>,
+    options: UrlPatternOptions,
+    /// Whether the bindings reject the arguments, like a base URL after the
+    /// options or next to an init.
+    invalid: bool,
+}
+
+fn arguments(values: &[Value]) -> Arguments<'_> {
+    let mut arguments = Arguments {
+        input: values.first(),
+        base_url: None,
+        options: UrlPatternOptions::default(),
+        invalid: false,
+    };
+    for (index, value) in values.iter().enumerate().skip(1) {
+        match value {
+            Value::String(base_url) => {
+                arguments.base_url = Some(base_url);
+                arguments.invalid |= index != 1 || values[0].is_object();
+            }
+            Value::Object(options) => {
+                arguments.options.ignore_case = options
+                    .get("ignoreCase")
+                    .and_then(Value::as_bool)
+                    .unwrap_or_default();
+            }
+            _ => {}
+        }
+    }
+    arguments
+}
+
+fn construct(arguments: &Arguments) -> Result<UrlPattern, String> {
+    if arguments.invalid {
+        return Err("invalid arguments".into());
+    }
+    match arguments.input {
+        Some(Value::String(input)) => {
+            UrlPattern::parse(input, arguments.base_url, &arguments.options)
+                .map_err(|error| error.to_string())
+        }
+        Some(init) => UrlPattern::from_init(&parse_init(init), &arguments.options)
+            .map_err(|error| error.to_string()),
+        None => UrlPattern::from_init(&UrlPatternInit::default(), &arguments.options)
+            .map_err(|error| error.to_string()),
+    }
+}
+
+/// The pattern string `urlpattern.any.js` expects for a component that the
+/// case doesn't list in `expected_obj`.
+fn default_pattern_string(case: &Value, component: &str, exactly_empty: bool) -> String {
+    let pattern = case["pattern"].as_array().unwrap();
+    let arguments = arguments(pattern);
+    let init = arguments
+        .input
+        .filter(|input| input.is_object())
+        .map(parse_init);
+
+    if exactly_empty {
+        return String::new();
+    }
+    if let Some(init) = &init {
+        if let Some(value) = init_field(init, component)
+            .as_ref()
+            .filter(|v| !v.is_empty())
+        {
+            return value.clone();
+        }
+        // A component after one that the init specifies defaults to `*`.
+        let index = COMPONENTS.iter().position(|&c| c == component).unwrap();
+        let earlier = COMPONENTS[..index]
+            .iter()
+            .filter(|&&c| !matches!(c, "username" | "password"))
+            .any(|c| init_field(init, c).is_some());
+        if earlier && !matches!(component, "username" | "password") {
+            return "*".into();
+        }
+    }
+
+    let base_url = arguments
+        .base_url
+        .map(String::from)
+        .or_else(|| init.and_then(|init| init.base_url));
+    if let Some(base_url) = base_url.filter(|_| !matches!(component, "username" | "password")) {
+        return base_url_component(&base_url, component).into();
+    }
+    "*".into()
+}
+
+fn exec(pattern: &UrlPattern, inputs: &[Value]) -> Result<Option<UrlPatternResult>, String> {
+    let arguments = arguments(inputs);
+    if arguments.invalid {
+        return Err("invalid arguments".into());
+    }
+    match arguments.input {
+        Some(Value::String(input)) => match arguments.base_url {
+            None => Ok(pattern.exec_url(input)),
+            #[cfg(feature = "url")]
+            Some(base_url) => Ok(url::Url::parse(base_url)
+                .and_then(|base_url| base_url.join(input))
+                .ok()
+                .and_then(|url| pattern.exec_parsed_url(&url))),
+            #[cfg(not(feature = "url"))]
+            Some(_) => Err("matching relative URLs needs the url feature".into()),
+        },
+        Some(init) => Ok(pattern.exec(&parse_init(init))),
+        None => Ok(pattern.exec(&UrlPatternInit::default())),
+    }
+}
+
+/// Checks a case, returning why it fails.
+fn run_case(case: &Value) -> Result<(), String> {
+    let exactly_empty: Vec<&str> = case["exactly_empty_components"]
+        .as_array()
+        .map(|components| components.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let pattern = construct(&arguments(case["pattern"].as_array().unwrap()));
+    let pattern = match (pattern, &case["expected_obj"]) {
+        (Err(_), Value::String(error)) if error == "error" => return Ok(()),
+        (Ok(_), Value::String(error)) if error == "error" => {
+            return Err("expected a construction error".into())
+        }
+        (Err(error), _) => return Err(format!("construction failed: {error}")),
+        (Ok(pattern), _) => pattern,
+    };
+
+    for component in COMPONENTS {
+        let expected = match case["expected_obj"].get(component).and_then(Value::as_str) {
+            Some(expected) => expected.to_string(),
+            None => default_pattern_string(case, component, exactly_empty.contains(&component)),
+        };
+        let actual = pattern_string(&pattern, component);
+        if actual != expected {
+            return Err(format!("{component} is {actual:?}, expected {expected:?}"));
+        }
+    }
+
+    let Some(inputs) = case["inputs"].as_array() else {
+        return Ok(());
+    };
+    let result = match (exec(&pattern, inputs), &case["expected_match"]) {
+        (Err(_), Value::String(error)) if error == "error" => return Ok(()),
+        (Ok(_), Value::String(error)) if error == "error" => {
+            return Err("expected exec to throw".into())
+        }
+        (Err(error), _) => return Err(error),
+        (Ok(result), _) => result,
+    };
+
+    let expected = &case["expected_match"];
+    let result = match (result, expected.is_null()) {
+        (None, true) => return Ok(()),
+        (Some(_), true) => return Err("expected no match".into()),
+        (None, false) => return Err("expected a match".into()),
+        (Some(result), false) => result,
+    };
+
+    for component in COMPONENTS {
+        let actual = component_result(&result, component);
+        let (input, groups) = match expected.get(component) {
+            Some(expected) => (
+                expected["input"].as_str().unwrap().to_string(),
+                expected["groups"]
+                    .as_object()
+                    .unwrap()
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.as_str().map(String::from)))
+                    .collect(),
+            ),
+            // An omitted component matched the empty string against `*`.
+            None if exactly_empty.contains(&component) => (String::new(), HashMap::new()),
+            None => (
+                String::new(),
+                HashMap::from([("0".to_string(), Some(String::new()))]),
+            ),
+        };
+        if actual.input != input || actual.groups != groups {
+            return Err(format!(
+                "{component} result is {:?} {:?}, expected {input:?} {groups:?}",
+                actual.input, actual.groups
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks [`BASE_URLS`] against the `url` crate.
+#[test]
+#[cfg(feature = "url")]
+fn base_urls() {
+    for (base_url, components) in BASE_URLS {
+        let url = url::Url::parse(base_url).unwrap();
+        let port = url.port().map(|port| port.to_string()).unwrap_or_default();
+        let parsed = [
+            url.scheme(),
+            url.host_str().unwrap_or_default(),
+            &port,
+            url.path(),
+            url.query().unwrap_or_default(),
+            url.fragment().unwrap_or_default(),
+        ];
+        assert_eq!(parsed, components, "{base_url}");
+    }
+}
+
+#[test]
+fn web_platform_tests() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/wpt");
+    let data = fs::read_to_string(root.join("urlpatterntestdata.json")).unwrap();
+    let cases: Vec<Value> = serde_json::from_str(&data).unwrap();
+
+    let mut report = String::new();
+    let mut failures = vec![];
+    for (index, case) in cases.iter().enumerate() {
+        let name = format!("{index} {} {}", case["pattern"], case["inputs"]);
+        match run_case(case) {
+            Ok(()) => writeln!(report, "PASS {name}").unwrap(),
+            Err(reason) => {
+                writeln!(report, "FAIL {name}: {reason}").unwrap();
+                failures.push(name);
+            }
+        }
+    }
+
+    let summary = format!(
+        "{} of {} cases passed, {} failed",
+        cases.len() - failures.len(),
+        cases.len(),
+        failures.len()
+    );
+    report.insert_str(0, &format!("{summary}\n\n"));
+    fs::write(
+        Path::new(env!("CARGO_TARGET_TMPDIR")).join("wpt-report.txt"),
+        &report,
+    )
+    .unwrap();
+    println!("{summary}");
+
+    let path = root.join(if cfg!(feature = "url") {
+        "expected-failures-url.txt"
+    } else {
+        "expected-failures.txt"
+    });
+    if std::env::var_os("URL_PATTERN_UPDATE_WPT").is_some() {
+        let list: String = failures.iter().map(|name| format!("{name}\n")).collect();
+        fs::write(&path, list).unwrap();
+    }
+    let failures: BTreeSet<String> = failures.into_iter().collect();
+    let expected: BTreeSet<String> = fs::read_to_string(&path)
+        .unwrap_or_default()
+        .lines()
+        .map(String::from)
+        .collect();
+
+    let regressions: Vec<_> = failures.difference(&expected).collect();
+    let fixed: Vec<_> = expected.difference(&failures).collect();
+    assert!(
+        regressions.is_empty() && fixed.is_empty(),
+        "cases that fail unexpectedly: {regressions:#?}\n\
+         cases that pass but are listed in {}: {fixed:#?}\n\
+         run with URL_PATTERN_UPDATE_WPT=1 to update the list",
+        path.display()
+    );
+}
@@ -0,0 +1,292 @@
+// SPDX-License-Identifier: MIT
+
+/*!
+Procedural macros for the [url-pattern](https://crates.io/crates/url-pattern) crate.
+*/
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{
+    parse_macro_input, Data, DeriveInput, Fields, GenericArgument, Ident, LitStr, PathArguments,
+    Token, Type,
+};
+use url_pattern::{Component, Constraints, Modifier, Options, Part};
+
+/// Returns the name of the outer type of `ty` if it is `Option<T>` or `Vec<T>`.
+fn container(ty: &Type) -> Option<String> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    if !matches!(arguments.args.first(), Some(GenericArgument::Type(_))) {
+        return None;
+    }
+    Some(segment.ident.to_string())
+}
+
+/// Derives `url_pattern::FromUrlPattern` for a struct with named fields.
+///
+/// Every field is filled from the group with the same name, or the name given
+/// with `#[url_pattern(group = "...")]`. `Option` fields are filled from
+/// groups with the `?` modifier and `Vec` fields from groups with the `*` or
+/// `+` modifier, split on the suffix and prefix of the group. Other fields are
+/// filled from groups without a modifier. Values are converted with `FromStr`.
+#[proc_macro_derive(FromUrlPattern, attributes(url_pattern))]
+pub fn derive_from_url_pattern(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match derive(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn derive(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "FromUrlPattern can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "FromUrlPattern needs a struct with named fields",
+        ));
+    };
+
+    let mut groups = vec![];
+    let mut initializers = vec![];
+    for field in &fields.named {
+        let ident = field.ident.as_ref().expect("named field");
+
+        let mut name = ident.to_string();
+        for attr in &field.attrs {
+            if !attr.path().is_ident("url_pattern") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("group") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `group = \"...\"`"))
+                }
+            })?;
+        }
+
+        let (kind, method) = match container(&field.ty).as_deref() {
+            Some("Option") => (quote!(Optional), quote!(optional)),
+            Some("Vec") => (quote!(Repeated), quote!(repeated)),
+            _ => (quote!(Required), quote!(required)),
+        };
+        groups.push(quote!((#name, ::url_pattern::GroupKind::#kind)));
+        initializers.push(quote!(#ident: groups.#method(#name)?));
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::url_pattern::FromUrlPattern for #ident #ty_generics #where_clause {
+            const GROUPS: &'static [(&'static str, ::url_pattern::GroupKind)] = &[#(#groups),*];
+
+            fn from_groups(
+                groups: &::url_pattern::Groups<'_>,
+            ) -> ::std::result::Result<Self, ::url_pattern::ExtractError> {
+                ::std::result::Result::Ok(Self {
+                    #(#initializers),*
+                })
+            }
+        }
+    })
+}
+
+/// The arguments of `url_pattern!`: option names followed by the pattern string.
+struct PatternInput {
+    options: Options,
+    options_tokens: TokenStream2,
+    pattern: LitStr,
+}
+
+impl Parse for PatternInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut options = Options::default();
+        let mut kind = quote!(default);
+        let mut ignore_case = false;
+        let mut constraints = false;
+
+        while input.peek(Ident) {
+            let ident: Ident = input.parse()?;
+            match ident.to_string().as_str() {
+                "pathname" => options = Options::pathname(),
+                "hostname" => options = Options::hostname(),
+                "default" => options = Options::default(),
+                "ignore_case" => ignore_case = true,
+                "constraints" => constraints = true,
+                _ => return Err(syn::Error::new_spanned(
+                    ident,
+                    "expected `pathname`, `hostname`, `default`, `ignore_case` or `constraints`",
+                )),
+            }
+            if ident != "ignore_case" && ident != "constraints" {
+                kind = quote!(#ident);
+            }
+            input.parse::<Token![,]>()?;
+        }
+        options.ignore_case = ignore_case;
+        if constraints {
+            options.constraints = Constraints::builtin();
+        }
+        let constraints = if constraints {
+            quote!(::url_pattern::Constraints::builtin())
+        } else {
+            quote!(::url_pattern::Constraints::new())
+        };
+
+        let pattern = input.parse()?;
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        }
+
+        Ok(PatternInput {
+            options,
+            options_tokens: quote! {
+                ::url_pattern::Options {
+                    ignore_case: #ignore_case,
+                    constraints: #constraints,
+                    ..::url_pattern::Options::#kind()
+                }
+            },
+            pattern,
+        })
+    }
+}
+
+fn string(value: &str) -> TokenStream2 {
+    quote!(::std::string::String::from(#value))
+}
+
+fn modifier(modifier: Option<Modifier>) -> TokenStream2 {
+    let variant = match modifier {
+        None => return quote!(::std::option::Option::None),
+        Some(Modifier::Optional) => quote!(Optional),
+        Some(Modifier::ZeroOrMore) => quote!(ZeroOrMore),
+        Some(Modifier::OneOrMore) => quote!(OneOrMore),
+    };
+    quote!(::std::option::Option::Some(::url_pattern::Modifier::#variant))
+}
+
+fn part(part: &Part) -> TokenStream2 {
+    let (variant, name, value, part_modifier, prefix, suffix) = match part {
+        Part::FixedText { value, modifier: m } => {
+            let (value, m) = (string(value), modifier(*m));
+            return quote!(::url_pattern::Part::FixedText { value: #value, modifier: #m });
+        }
+        Part::SegmentWildcard {
+            name,
+            modifier,
+            prefix,
+            suffix,
+        } => (
+            quote!(SegmentWildcard),
+            name,
+            None,
+            modifier,
+            prefix,
+            suffix,
+        ),
+        Part::FullWildcard {
+            name,
+            modifier,
+            prefix,
+            suffix,
+        } => (quote!(FullWildcard), name, None, modifier, prefix, suffix),
+        Part::RegExp {
+            name,
+            value,
+            modifier,
+            prefix,
+            suffix,
+            constraint,
+        } => (
+            quote!(RegExp),
+            name,
+            Some((value, constraint)),
+            modifier,
+            prefix,
+            suffix,
+        ),
+    };
+
+    let name = string(name);
+    let value = value.map(|(value, constraint)| {
+        let value = string(value);
+        let constraint = match constraint {
+            Some(constraint) => {
+                let constraint = string(constraint);
+                quote!(::std::option::Option::Some(#constraint))
+            }
+            None => quote!(::std::option::Option::None),
+        };
+        quote!(value: #value, constraint: #constraint,)
+    });
+    let part_modifier = modifier(*part_modifier);
+    let prefix = string(prefix);
+    let suffix = string(suffix);
+    quote! {
+        ::url_pattern::Part::#variant {
+            name: #name,
+            #value
+            modifier: #part_modifier,
+            prefix: #prefix,
+            suffix: #suffix,
+        }
+    }
+}
+
+/// Parses a pattern string at compile time and expands to a
+/// `&'static url_pattern::Component` for it.
+///
+/// The pattern string can be preceded by the options to compile it with:
+/// `pathname`, `hostname` or `default` (the default), `ignore_case`, and
+/// `constraints` for the builtin constraints like `:id<int>`.
+///
+/// ```ignore
+/// let users = url_pattern!(pathname, constraints, "/users/:id<int>");
+/// ```
+///
+/// An invalid pattern string is a compile error. The expansion is a `static`
+/// that builds the component from the parsed parts on first use, so the
+/// pattern string is never parsed at runtime and the regular expression is
+/// compiled only once.
+#[proc_macro]
+pub fn url_pattern(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as PatternInput);
+
+    let component = match Component::compile(&input.pattern.value(), &input.options) {
+        Ok(component) => component,
+        Err(err) => {
+            return syn::Error::new_spanned(&input.pattern, format!("invalid pattern: {err}"))
+                .to_compile_error()
+                .into();
+        }
+    };
+
+    let parts = component.parts().iter().map(part);
+    let options = input.options_tokens;
+    quote! {
+        {
+            static COMPONENT: ::std::sync::LazyLock<::url_pattern::Component> =
+                ::std::sync::LazyLock::new(|| {
+                    ::url_pattern::Component::from_parts(::std::vec![#(#parts),*], &#options)
+                        .expect("the pattern was validated at compile time")
+                });
+            &*COMPONENT
+        }
+    }
+    .into()
+}
@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: MIT
+
+use url_pattern::{Component, ExtractError, FromUrlPattern, Options};
+use url_pattern_macros::FromUrlPattern;
+
+#[derive(FromUrlPattern, Debug, PartialEq)]
+struct UserRoute {
+    id: u64,
+    tab: Option<String>,
+    rest: Vec<String>,
+}
+
+#[derive(FromUrlPattern, Debug, PartialEq)]
+struct StaticRoute {
+    #[url_pattern(group = "0")]
+    path: String,
+}
+
+#[test]
+fn extract() {
+    let component =
+        Component::compile("/users/:id{/:tab}?/files/:rest*", &Options::pathname()).unwrap();
+
+    assert_eq!(
+        component.extract::<UserRoute>("/users/42/files/a/b"),
+        Some(Ok(UserRoute {
+            id: 42,
+            tab: None,
+            rest: vec!["a".into(), "b".into()],
+        }))
+    );
+    assert_eq!(
+        component.extract::<UserRoute>("/users/7/settings/files"),
+        Some(Ok(UserRoute {
+            id: 7,
+            tab: Some("settings".into()),
+            rest: vec![],
+        }))
+    );
+    assert_eq!(component.extract::<UserRoute>("/posts/1"), None);
+
+    let Some(Err(ExtractError::Parse { name, value, .. })) =
+        component.extract::<UserRoute>("/users/me/files")
+    else {
+        panic!("expected a parse error");
+    };
+    assert_eq!((name.as_str(), value.as_str()), ("id", "me"));
+
+    let component = Component::compile("/static/*", &Options::pathname()).unwrap();
+    assert_eq!(
+        component.extract::<StaticRoute>("/static/css/app.css"),
+        Some(Ok(StaticRoute {
+            path: "css/app.css".into()
+        }))
+    );
+}
+
+#[derive(FromUrlPattern, Debug, PartialEq)]
+struct TagRoute {
+    tags: Vec<String>,
+}
+
+#[test]
+fn repeated() {
+    let extract = |pattern: &str, input: &str| {
+        Component::compile(pattern, &Options::pathname())
+            .unwrap()
+            .extract::<TagRoute>(input)
+            .unwrap()
+            .unwrap()
+            .tags
+    };
+
+    // Split on the prefix and suffix of the group, not on the delimiter.
+    assert_eq!(extract("/tags{-:tags}+", "/tags-a-b"), ["a", "b"]);
+    assert_eq!(extract("/tags{[:tags]}*", "/tags[a][b-c]"), ["a", "b-c"]);
+    assert_eq!(extract("/tags/{:tags}+", "/tags/a-b"), ["a-b"]);
+    assert_eq!(extract("/tags/:tags+", "/tags/a/b"), ["a", "b"]);
+}
+
+#[test]
+fn check() {
+    let check = |pattern: &str| {
+        UserRoute::check(&Component::compile(pattern, &Options::pathname()).unwrap())
+    };
+
+    assert_eq!(check("/users/:id/:tab?/:rest+"), Ok(()));
+    assert_eq!(
+        check("/users/:id/:tab?"),
+        Err(ExtractError::UnknownGroup("rest".into()))
+    );
+    assert_eq!(
+        check("/users/:id/:tab/:rest*"),
+        Err(ExtractError::Modifier {
+            name: "tab".into(),
+            expected: "the `?` modifier"
+        })
+    );
+}
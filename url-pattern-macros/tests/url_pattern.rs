@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: MIT
+
+use url_pattern::{Component, Options};
+use url_pattern_macros::url_pattern;
+
+#[test]
+fn expands_to_component() {
+    let users = url_pattern!(pathname, "/users/:id(\\d+){/:tab}?");
+    let expected = Component::compile("/users/:id(\\d+){/:tab}?", &Options::pathname()).unwrap();
+    assert_eq!(users.parts(), expected.parts());
+    assert_eq!(users.regexp(), expected.regexp());
+    assert!(users.test("/users/42/settings"));
+
+    let host = url_pattern!(hostname, ignore_case, "*.example.com");
+    assert!(host.options().ignore_case);
+    assert!(host.test("API.Example.com"));
+
+    let any = url_pattern!(":foo");
+    assert!(any.test("a/b"));
+}
+
+#[test]
+fn expands_to_static() {
+    let component = || url_pattern!(pathname, "/users/:id");
+    assert!(std::ptr::eq(component(), component()));
+}
+
+#[test]
+fn constraints() {
+    let users = url_pattern!(pathname, constraints, "/users/:id<int>");
+    assert_eq!(users.pattern_string(), "/users/:id<int>");
+    assert!(users.test("/users/42"));
+    // The validation function of `int` runs too.
+    assert!(!users.test("/users/99999999999999999999"));
+
+    // Without `constraints`, `<` is fixed text.
+    let literal = url_pattern!(pathname, "/users/:id<int>");
+    assert!(literal.test("/users/42<int>"));
+}